@@ -19,6 +19,23 @@ fn bench_fill_uninit<const N: usize>() {
     test::black_box(buf);
 }
 
+// Capacity of the reservoir used by `bench_reservoir_fill`
+#[cfg(feature = "std")]
+const RESERVOIR_CAPACITY: usize = 64 * 1024;
+
+// Take bytes from a `Reservoir`, refilling it with a single `fill` call once
+// it runs out. The refill cost is amortized over many requests.
+#[cfg(feature = "std")]
+#[inline(always)]
+fn bench_reservoir_fill<const N: usize>(reservoir: &mut getrandom::Reservoir) {
+    let mut buf = [0u8; N];
+    if reservoir.fill(&mut buf).is_err() {
+        reservoir.refill().unwrap();
+        reservoir.fill(&mut buf).unwrap();
+    }
+    test::black_box(&buf[..]);
+}
+
 // We benchmark using #[inline(never)] "inner" functions for two reasons:
 //  - Avoiding inlining reduces a source of variance when running benchmarks.
 //  - It is _much_ easier to get the assembly or IR for the inner loop.
@@ -49,6 +66,18 @@ macro_rules! bench {
                 b.bytes = $size as u64;
                 b.iter(inner);
             }
+            #[cfg(feature = "std")]
+            #[bench]
+            pub fn bench_reservoir_fill(b: &mut test::Bencher) {
+                #[inline(never)]
+                fn inner(reservoir: &mut getrandom::Reservoir) {
+                    super::bench_reservoir_fill::<{ $size }>(reservoir)
+                }
+
+                let mut reservoir = getrandom::Reservoir::new(super::RESERVOIR_CAPACITY).unwrap();
+                b.bytes = $size as u64;
+                b.iter(|| inner(&mut reservoir));
+            }
         }
     };
 }
@@ -69,3 +98,31 @@ bench!(p384, 384 / 8);
 // this should normally be done by a userspace CSPRNG. However, we have a test
 // here to see the effects of a lower (amortized) syscall overhead.
 bench!(page, 4096);
+
+// The largest request served by the `aesni_drbg` and `chacha20` backends in one
+// step, used to compare the throughput of userspace generators for bulk output.
+bench!(bulk, 64 * 1024);
+
+// Word-sized values used for seeding, e.g. by `rand::rngs::SmallRng`. Some
+// backends implement these without going through the buffer path.
+#[bench]
+pub fn bench_u32(b: &mut test::Bencher) {
+    #[inline(never)]
+    fn inner() -> u32 {
+        getrandom::u32().unwrap()
+    }
+
+    b.bytes = 4;
+    b.iter(inner);
+}
+
+#[bench]
+pub fn bench_u64(b: &mut test::Bencher) {
+    #[inline(never)]
+    fn inner() -> u64 {
+        getrandom::u64().unwrap()
+    }
+
+    b.bytes = 8;
+    b.iter(inner);
+}
@@ -1,9 +1,172 @@
-// Automatically detect cfg(sanitize = "memory") even if cfg(sanitize) isn't
-// supported. Build scripts get cfg() info, even if the cfg is unstable.
+use std::{env, fs, path::PathBuf, process::Command};
+
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
-    let santizers = std::env::var("CARGO_CFG_SANITIZE").unwrap_or_default();
+
+    // Automatically detect cfg(sanitize = "memory") even if cfg(sanitize) isn't
+    // supported. Build scripts get cfg() info, even if the cfg is unstable.
+    let santizers = env::var("CARGO_CFG_SANITIZE").unwrap_or_default();
     if santizers.contains("memory") {
         println!("cargo:rustc-cfg=getrandom_msan");
     }
+
+    // The `stdrng` backend uses the unstable `std::random` module if the
+    // compiler provides it for the target, otherwise the default backend is used.
+    let mut backend = env::var("CARGO_CFG_GETRANDOM_BACKEND").unwrap_or_default();
+    if backend == "stdrng" {
+        if std_random_available() == Some(true) {
+            println!("cargo:rustc-cfg=getrandom_std_random");
+        } else {
+            println!(
+                "cargo:warning=`std::random` is not available for the target, \
+                 `stdrng` backend is ignored and the default backend is used"
+            );
+            backend.clear();
+        }
+    }
+
+    // The default UEFI backend and `efi_rng_with_fallback` use the unstable
+    // `std::os::uefi`.
+    let uefi_std_backends = ["", "efi_rng", "efi_rng_with_fallback"];
+    if cfg("target_os") == "uefi" && uefi_std_backends.contains(&backend.as_str()) {
+        println!("cargo:rustc-cfg=getrandom_uefi_std");
+    }
+
+    // The `chain` backend tries the sources listed in `getrandom_backend_order`.
+    let chain_sources = if backend == "chain" {
+        chain_sources()
+    } else {
+        Vec::new()
+    };
+
+    // The internal errors of the RDRAND and RNDR sources are defined only if the
+    // selected backend includes `rdrand.rs` or `rndr.rs`.
+    for source in hw_sources(&backend, &chain_sources) {
+        println!("cargo:rustc-cfg=getrandom_uses_{}", source);
+    }
+}
+
+/// Get the value of a configuration option, or an empty string if it is not set.
+fn cfg(name: &str) -> String {
+    env::var(format!("CARGO_CFG_{}", name.to_uppercase())).unwrap_or_default()
+}
+
+/// Sources supported by the `chain` backend
+const CHAIN_SOURCES: &[&str] = &["linux_getrandom", "use_file", "rdrand", "rndr", "custom"];
+
+/// Parse `getrandom_backend_order`, enable the listed sources of the `chain`
+/// backend and generate the list of their `fill_inner` functions.
+///
+/// Invalid orders are reported with `compile_error!` in the generated code.
+fn chain_sources() -> Vec<String> {
+    let res = parse_chain_order(&cfg("getrandom_backend_order"));
+    let code = match &res {
+        Ok(sources) => {
+            let fns: Vec<String> = sources
+                .iter()
+                .map(|source| format!("{}::fill_inner", source))
+                .collect();
+            format!("const SOURCES: &[FillFn] = &[{}];\n", fns.join(", "))
+        }
+        Err(msg) => format!(
+            "compile_error!({:?});\nconst SOURCES: &[FillFn] = &[];\n",
+            msg
+        ),
+    };
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").expect("OUT_DIR is not set"));
+    fs::write(out_dir.join("chain_sources.rs"), code).expect("failed to write chain_sources.rs");
+
+    let sources = res.unwrap_or_default();
+    for source in &sources {
+        println!("cargo:rustc-cfg=getrandom_chain_source=\"{}\"", source);
+    }
+    sources
+}
+
+/// Split a comma-separated `getrandom_backend_order` value into sources.
+fn parse_chain_order(order: &str) -> Result<Vec<String>, String> {
+    let mut sources: Vec<String> = Vec::new();
+    for source in order.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        if !CHAIN_SOURCES.contains(&source) {
+            return Err(format!(
+                "unknown source `{}` in `getrandom_backend_order`, expected one of: {}",
+                source,
+                CHAIN_SOURCES.join(", ")
+            ));
+        }
+        if sources.iter().any(|s| s == source) {
+            return Err(format!(
+                "source `{}` is listed more than once in `getrandom_backend_order`",
+                source
+            ));
+        }
+        sources.push(source.to_string());
+    }
+    if sources.is_empty() {
+        return Err("`chain` backend requires a non-empty `getrandom_backend_order` value".into());
+    }
+    Ok(sources)
+}
+
+/// Hardware sources (`"rdrand"` and `"rndr"`) included by the selected backend.
+fn hw_sources(backend: &str, chain_sources: &[String]) -> Vec<String> {
+    let arch = cfg("target_arch");
+    let x86 = arch == "x86_64" || arch == "x86";
+    let hybrid_hw = if x86 { "rdrand" } else { "rndr" };
+    let os = cfg("target_os");
+    // Sources included by the backend, plus the backend itself
+    let mut sources = match backend {
+        "combined" => vec![cfg("getrandom_combine_a"), cfg("getrandom_combine_b")],
+        "chain" => chain_sources.to_vec(),
+        "aesni_drbg" | "chacha20" => vec![cfg("getrandom_drbg_seed")],
+        "dual_check" => vec![cfg("getrandom_dual_check_source")],
+        "efi_rng_with_fallback" => vec!["rdrand".to_string()],
+        "hybrid_hw_os" => vec![hybrid_hw.to_string(), cfg("getrandom_hybrid_os")],
+        // RDRAND is the default backend on SGX
+        "" if arch == "x86_64" && cfg("target_env") == "sgx" => vec!["rdrand".to_string()],
+        // The default backends on Linux and Android use `linux_android*.rs`
+        "" if os == "linux" || os == "android" => vec!["linux_getrandom".to_string()],
+        _ => vec![backend.to_string()],
+    };
+    // `linux_android*.rs` include RDRAND with `getrandom_linux_rdrand_mix`
+    let linux = sources
+        .iter()
+        .any(|s| s == "linux_getrandom" || s == "getrandom_libc");
+    if linux && env::var_os("CARGO_CFG_GETRANDOM_LINUX_RDRAND_MIX").is_some() {
+        sources.push("rdrand".to_string());
+    }
+    sources.retain(|s| s == "rdrand" || s == "rndr");
+    sources.sort();
+    sources.dedup();
+    sources
+}
+
+/// Check if a probe using `std::random` compiles for the target.
+fn std_random_available() -> Option<bool> {
+    const PROBE: &str = r#"
+        #![feature(random)]
+        pub fn probe(buf: &mut [u8]) {
+            use std::random::{DefaultRandomSource, RandomSource};
+            DefaultRandomSource.fill_bytes(buf)
+        }
+    "#;
+
+    let rustc = env::var_os("RUSTC")?;
+    let target = env::var_os("TARGET")?;
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR")?);
+    // Use the same flags as the crate build, e.g. `--sysroot`
+    let rustflags = env::var("CARGO_ENCODED_RUSTFLAGS").unwrap_or_default();
+    let probe_path = out_dir.join("probe_std_random.rs");
+    fs::write(&probe_path, PROBE).ok()?;
+    let output = Command::new(rustc)
+        .args(["--crate-type=lib", "--emit=metadata", "--edition=2021"])
+        .arg("--target")
+        .arg(target)
+        .arg("--out-dir")
+        .arg(&out_dir)
+        .arg(&probe_path)
+        .args(rustflags.split('\x1f').filter(|flag| !flag.is_empty()))
+        .output()
+        .ok()?;
+    Some(output.status.success())
 }
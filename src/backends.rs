@@ -5,6 +5,17 @@
 //! The function MUST fully initialize `dest` when `Ok(())` is returned.
 //! The function MUST NOT ever write uninitialized bytes into `dest`,
 //! regardless of what value it returns.
+//!
+//! It should also provide `MAX_FILL_CHUNK: usize`, the maximum number of
+//! bytes requested from the system in a single call (`usize::MAX` if there
+//! is no such limit), and `fn init_inner() -> Result<(), Error>`, which
+//! eagerly performs any lazy initialization done by the first `fill_inner`
+//! call and returns the error it would return (a no-op by default).
+//!
+//! Finally, it should provide `fn fill_nonblocking_inner(dest: &mut
+//! [MaybeUninit<u8>]) -> Result<(), Error>`, which has the same requirements
+//! as `fill_inner`, but returns `Error::WOULD_BLOCK` instead of blocking until
+//! the source is ready (same as `fill_inner` by default).
 
 cfg_if! {
     if #[cfg(getrandom_backend = "custom")] {
@@ -13,22 +24,87 @@ cfg_if! {
     } else if #[cfg(getrandom_backend = "linux_getrandom")] {
         mod linux_android;
         pub use linux_android::*;
+    } else if #[cfg(getrandom_backend = "getrandom_libc")] {
+        mod linux_android_syscall;
+        pub use linux_android_syscall::*;
     } else if #[cfg(getrandom_backend = "rdrand")] {
         mod rdrand;
         pub use rdrand::*;
     } else if #[cfg(getrandom_backend = "rndr")] {
         mod rndr;
         pub use rndr::*;
+    } else if #[cfg(getrandom_backend = "riscv_zkr")] {
+        mod riscv_zkr;
+        pub use riscv_zkr::*;
+    } else if #[cfg(getrandom_backend = "openbsd_arc4random")] {
+        mod openbsd_arc4random;
+        pub use openbsd_arc4random::*;
+    } else if #[cfg(getrandom_backend = "fuchsia")] {
+        mod fuchsia;
+        pub use fuchsia::*;
+    } else if #[cfg(getrandom_backend = "host_call")] {
+        mod host_call;
+        pub use host_call::*;
     } else if #[cfg(getrandom_backend = "wasm_js")] {
         mod wasm_js;
         pub use wasm_js::*;
+    } else if #[cfg(getrandom_backend = "verifier")] {
+        mod verifier;
+        pub use verifier::*;
+    } else if #[cfg(getrandom_backend = "deterministic")] {
+        mod deterministic;
+        pub use deterministic::*;
+    } else if #[cfg(getrandom_backend = "counter")] {
+        mod counter;
+        pub use counter::*;
+    } else if #[cfg(getrandom_backend = "mock")] {
+        mod mock;
+        pub use mock::*;
+    } else if #[cfg(getrandom_backend = "panic")] {
+        mod panic;
+        pub use panic::*;
+    } else if #[cfg(getrandom_backend = "proxy")] {
+        mod proxy;
+        pub use proxy::*;
+    } else if #[cfg(getrandom_backend = "test_failing")] {
+        mod test_failing;
+        pub use test_failing::*;
+    } else if #[cfg(getrandom_backend = "test_slow")] {
+        mod test_slow;
+        pub use test_slow::*;
+    } else if #[cfg(all(getrandom_backend = "stdrng", getrandom_std_random))] {
+        mod stdrng;
+        pub use stdrng::*;
+    } else if #[cfg(getrandom_backend = "chain")] {
+        mod chain;
+        pub use chain::*;
+    } else if #[cfg(getrandom_backend = "aesni_drbg")] {
+        mod aesni_drbg;
+        pub use aesni_drbg::*;
+    } else if #[cfg(getrandom_backend = "chacha20")] {
+        mod chacha20;
+        pub use chacha20::*;
+    } else if #[cfg(getrandom_backend = "hybrid_hw_os")] {
+        mod hybrid_hw_os;
+        pub use hybrid_hw_os::*;
+    } else if #[cfg(getrandom_backend = "dual_check")] {
+        mod dual_check;
+        pub use dual_check::*;
+    } else if #[cfg(getrandom_backend = "combined")] {
+        mod combined;
+        pub use combined::*;
+    } else if #[cfg(getrandom_backend = "efi_rng")] {
+        mod efi_rng;
+        pub use efi_rng::*;
+    } else if #[cfg(getrandom_backend = "efi_rng_with_fallback")] {
+        mod efi_rng_with_fallback;
+        pub use efi_rng_with_fallback::*;
     } else if #[cfg(target_os = "espidf")] {
         mod esp_idf;
         pub use esp_idf::*;
     } else if #[cfg(any(
         target_os = "haiku",
         target_os = "redox",
-        target_os = "nto",
         target_os = "aix",
     ))] {
         mod use_file;
@@ -38,13 +114,21 @@ cfg_if! {
         target_os = "openbsd",
         target_os = "vita",
         target_os = "emscripten",
+        all(
+            getrandom_apple_getentropy,
+            any(
+                target_os = "ios",
+                target_os = "visionos",
+                target_os = "watchos",
+                target_os = "tvos",
+            ),
+        ),
+        all(getrandom_freebsd_getentropy, target_os = "freebsd"),
     ))] {
         mod getentropy;
         pub use getentropy::*;
     } else if #[cfg(any(
         target_os = "dragonfly",
-        target_os = "freebsd",
-        target_os = "hurd",
         target_os = "illumos",
         // Check for target_arch = "arm" to only include the 3DS. Does not
         // include the Nintendo Switch (which is target_arch = "aarch64").
@@ -95,12 +179,20 @@ cfg_if! {
     } else if #[cfg(any(target_os = "android", target_os = "linux"))] {
         mod linux_android;
         pub use linux_android::*;
+    } else if #[cfg(target_os = "nto")] {
+        mod use_file;
+        mod nto;
+        pub use nto::*;
+    } else if #[cfg(target_os = "hurd")] {
+        mod use_file;
+        mod hurd;
+        pub use hurd::*;
     } else if #[cfg(target_os = "solaris")] {
         mod solaris;
         pub use solaris::*;
-    } else if #[cfg(target_os = "netbsd")] {
-        mod netbsd;
-        pub use netbsd::*;
+    } else if #[cfg(any(target_os = "freebsd", target_os = "netbsd"))] {
+        mod bsd_arandom;
+        pub use bsd_arandom::*;
     } else if #[cfg(target_os = "fuchsia")] {
         mod fuchsia;
         pub use fuchsia::*;
@@ -120,9 +212,12 @@ cfg_if! {
             } else if #[cfg(target_env = "p2")] {
                 mod wasi_p2;
                 pub use wasi_p2::*;
+            } else if #[cfg(target_env = "p3")] {
+                mod wasi_p3;
+                pub use wasi_p3::*;
             } else {
                 compile_error!(
-                    "Unknown version of WASI (only previews 1 and 2 are supported) \
+                    "Unknown version of WASI (only previews 1, 2 and 3 are supported) \
                     or Rust version older than 1.80 was used"
                 );
             }
@@ -142,6 +237,9 @@ cfg_if! {
     } else if #[cfg(windows)] {
         mod windows;
         pub use windows::*;
+    } else if #[cfg(target_os = "uefi")] {
+        mod efi_rng;
+        pub use efi_rng::*;
     } else if #[cfg(all(target_arch = "x86_64", target_env = "sgx"))] {
         mod rdrand;
         pub use rdrand::*;
@@ -0,0 +1,395 @@
+//! NIST SP 800-90A Rev. 1 CTR_DRBG (AES-256, no derivation function) using AES-NI
+//!
+//! The DRBG is instantiated from, and periodically reseeded with, 48 bytes of
+//! entropy from the source selected with the `getrandom_drbg_seed` configuration
+//! flag. Supported values are `"linux_getrandom"`, `"use_file"`, `"rdrand"` and
+//! `"custom"`.
+//!
+//! All AES operations use the AES-NI instructions, there is no software
+//! fallback. If they are not supported by the CPU, all calls fail with
+//! [`Error::NO_AESNI`].
+//!
+//! The state is shared by all threads and protected by a spin lock. On Linux and
+//! Android the DRBG is also reseeded after `fork`, which is detected by
+//! comparing process IDs.
+//!
+//! This backend and `chacha20` are opt-in userspace generators: they keep their
+//! state in process memory and are only used when selected explicitly.
+// The seed source may include its own copy of `lazy`
+#![allow(clippy::duplicate_mod)]
+use crate::{
+    util::{slice_as_uninit, slice_as_uninit_mut, volatile_zero},
+    Error,
+};
+use core::{
+    cell::UnsafeCell,
+    mem::{transmute, MaybeUninit},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+pub use crate::util::{fill_nonblocking_inner, init_inner, inner_u32, inner_u64};
+
+#[path = "../lazy.rs"]
+mod lazy;
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "x86")))]
+compile_error!("`aesni_drbg` backend can be enabled only for x86 and x86-64 targets!");
+
+cfg_if! {
+    if #[cfg(getrandom_drbg_seed = "linux_getrandom")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` is used
+        #[path = "linux_android.rs"]
+        mod seed_source;
+    } else if #[cfg(getrandom_drbg_seed = "use_file")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` is used
+        #[path = "use_file.rs"]
+        mod seed_source;
+    } else if #[cfg(getrandom_drbg_seed = "rdrand")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` is used
+        #[path = "rdrand.rs"]
+        mod seed_source;
+    } else if #[cfg(getrandom_drbg_seed = "custom")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` is used
+        #[path = "custom.rs"]
+        mod seed_source;
+    } else {
+        compile_error!("`aesni_drbg` backend requires a valid `getrandom_drbg_seed` value!");
+    }
+}
+
+cfg_if! {
+    if #[cfg(target_arch = "x86_64")] {
+        use core::arch::x86_64 as arch;
+    } else if #[cfg(target_arch = "x86")] {
+        use core::arch::x86 as arch;
+    }
+}
+
+use arch::{
+    __m128i, _mm_aesenc_si128, _mm_aesenclast_si128, _mm_aeskeygenassist_si128, _mm_shuffle_epi32,
+    _mm_slli_si128, _mm_xor_si128,
+};
+
+/// Maximum number of bytes per request (SP 800-90A, Table 3: 2^19 bits).
+pub const MAX_FILL_CHUNK: usize = 1 << 16;
+
+/// Number of requests after which the DRBG is reseeded. Table 3 allows up to
+/// 2^48, we reseed much more often to limit the impact of a state compromise.
+const RESEED_INTERVAL: u64 = 1 << 16;
+
+const KEY_LEN: usize = 32;
+const BLOCK_LEN: usize = 16;
+const SEED_LEN: usize = KEY_LEN + BLOCK_LEN;
+
+type RoundKeys = [__m128i; 15];
+
+/// Expand an AES-256 key into the round keys used for encryption.
+#[target_feature(enable = "aes")]
+unsafe fn expand_key(key: &[u8; KEY_LEN]) -> RoundKeys {
+    /// XOR all preceding words of `prev` into each of its words, then XOR `t`.
+    #[inline(always)]
+    unsafe fn mix(prev: __m128i, t: __m128i) -> __m128i {
+        let prev = _mm_xor_si128(prev, _mm_slli_si128::<4>(prev));
+        let prev = _mm_xor_si128(prev, _mm_slli_si128::<8>(prev));
+        _mm_xor_si128(prev, t)
+    }
+
+    let [k0, k1] = transmute::<[u8; KEY_LEN], [[u8; BLOCK_LEN]; 2]>(*key);
+    let mut keys: RoundKeys = [transmute::<[u8; BLOCK_LEN], __m128i>(k0); 15];
+    keys[1] = transmute::<[u8; BLOCK_LEN], __m128i>(k1);
+
+    // Even round keys apply RotWord, SubWord and Rcon to the last word of the
+    // previous round key, odd ones only apply SubWord.
+    macro_rules! even {
+        ($i:literal, $rcon:literal) => {
+            let t = _mm_aeskeygenassist_si128::<$rcon>(keys[$i - 1]);
+            keys[$i] = mix(keys[$i - 2], _mm_shuffle_epi32::<0xff>(t));
+        };
+    }
+    macro_rules! odd {
+        ($i:literal) => {
+            let t = _mm_aeskeygenassist_si128::<0x00>(keys[$i - 1]);
+            keys[$i] = mix(keys[$i - 2], _mm_shuffle_epi32::<0xaa>(t));
+        };
+    }
+    even!(2, 0x01);
+    odd!(3);
+    even!(4, 0x02);
+    odd!(5);
+    even!(6, 0x04);
+    odd!(7);
+    even!(8, 0x08);
+    odd!(9);
+    even!(10, 0x10);
+    odd!(11);
+    even!(12, 0x20);
+    odd!(13);
+    even!(14, 0x40);
+    keys
+}
+
+#[target_feature(enable = "aes")]
+unsafe fn encrypt(keys: &RoundKeys, block: [u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+    let mut state = _mm_xor_si128(transmute::<[u8; BLOCK_LEN], __m128i>(block), keys[0]);
+    for key in &keys[1..14] {
+        state = _mm_aesenc_si128(state, *key);
+    }
+    let state = _mm_aesenclast_si128(state, keys[14]);
+    transmute::<__m128i, [u8; BLOCK_LEN]>(state)
+}
+
+/// CTR_DRBG state (SP 800-90A, Section 10.2.1.1)
+struct Drbg {
+    key: [u8; KEY_LEN],
+    v: u128,
+    reseed_counter: u64,
+}
+
+impl Drbg {
+    /// CTR_DRBG_Instantiate_algorithm (Section 10.2.1.3.1)
+    ///
+    /// `seed_material` is the entropy input XORed with the personalization string.
+    #[target_feature(enable = "aes")]
+    unsafe fn new(seed_material: &[u8; SEED_LEN]) -> Self {
+        let mut drbg = Drbg {
+            key: [0; KEY_LEN],
+            v: 0,
+            reseed_counter: 1,
+        };
+        drbg.update(seed_material);
+        drbg
+    }
+
+    /// CTR_DRBG_Reseed_algorithm (Section 10.2.1.4.1)
+    ///
+    /// `seed_material` is the entropy input XORed with the additional input.
+    #[target_feature(enable = "aes")]
+    unsafe fn reseed(&mut self, seed_material: &[u8; SEED_LEN]) {
+        self.update(seed_material);
+        self.reseed_counter = 1;
+    }
+
+    /// CTR_DRBG_Update (Section 10.2.1.2)
+    #[target_feature(enable = "aes")]
+    unsafe fn update(&mut self, provided_data: &[u8; SEED_LEN]) {
+        let keys = expand_key(&self.key);
+        let mut temp = [0u8; SEED_LEN];
+        for block in temp.chunks_exact_mut(BLOCK_LEN) {
+            self.v = self.v.wrapping_add(1);
+            block.copy_from_slice(&encrypt(&keys, self.v.to_be_bytes()));
+        }
+        for (t, p) in temp.iter_mut().zip(provided_data) {
+            *t ^= p;
+        }
+        let (key, v) = temp.split_at(KEY_LEN);
+        self.key.copy_from_slice(key);
+        let mut v_bytes = [0u8; BLOCK_LEN];
+        v_bytes.copy_from_slice(v);
+        self.v = u128::from_be_bytes(v_bytes);
+        volatile_zero(&mut temp);
+    }
+
+    /// CTR_DRBG_Generate_algorithm (Section 10.2.1.5.1) without additional input
+    ///
+    /// The caller is responsible for reseeding once `reseed_counter` exceeds
+    /// `RESEED_INTERVAL` and for limiting `dest` to `MAX_FILL_CHUNK` bytes.
+    #[target_feature(enable = "aes")]
+    unsafe fn generate(&mut self, dest: &mut [MaybeUninit<u8>]) {
+        let keys = expand_key(&self.key);
+        for chunk in dest.chunks_mut(BLOCK_LEN) {
+            self.v = self.v.wrapping_add(1);
+            let block = encrypt(&keys, self.v.to_be_bytes());
+            chunk.copy_from_slice(slice_as_uninit(&block[..chunk.len()]));
+        }
+        self.update(&[0; SEED_LEN]);
+        self.reseed_counter += 1;
+    }
+}
+
+cfg_if! {
+    if #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        not(target_env = ""),
+    ))] {
+        type ProcessId = libc::pid_t;
+
+        fn process_id() -> ProcessId {
+            unsafe { libc::getpid() }
+        }
+    } else {
+        type ProcessId = u8;
+
+        fn process_id() -> ProcessId {
+            0
+        }
+    }
+}
+
+struct State {
+    drbg: Drbg,
+    /// ID of the process which last (re)seeded `drbg`
+    pid: ProcessId,
+}
+
+/// Spin lock protecting the global state
+struct Lock {
+    locked: AtomicBool,
+    state: UnsafeCell<Option<State>>,
+}
+
+// SAFETY: `state` is only accessed while holding `locked`.
+unsafe impl Sync for Lock {}
+
+static STATE: Lock = Lock {
+    locked: AtomicBool::new(false),
+    state: UnsafeCell::new(None),
+};
+
+struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        STATE.locked.store(false, Ordering::Release);
+    }
+}
+
+fn with_state<T>(f: impl FnOnce(&mut Option<State>) -> T) -> T {
+    while STATE
+        .locked
+        .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+        .is_err()
+    {
+        core::hint::spin_loop();
+    }
+    let _guard = Guard;
+    // SAFETY: we hold the lock until `_guard` is dropped.
+    f(unsafe { &mut *STATE.state.get() })
+}
+
+fn get_seed() -> Result<[u8; SEED_LEN], Error> {
+    let mut seed = [0u8; SEED_LEN];
+    // SAFETY: `fill_inner` never writes uninitialized bytes.
+    seed_source::fill_inner(unsafe { slice_as_uninit_mut(&mut seed) })?;
+    Ok(seed)
+}
+
+#[target_feature(enable = "aes")]
+unsafe fn fill_chunk(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    let pid = process_id();
+    loop {
+        let done = with_state(|state| match state {
+            Some(s) if s.pid == pid && s.drbg.reseed_counter <= RESEED_INTERVAL => {
+                s.drbg.generate(dest);
+                true
+            }
+            _ => false,
+        });
+        if done {
+            return Ok(());
+        }
+        // The seed source may block, so it is called without holding the lock.
+        let mut seed = get_seed()?;
+        with_state(|state| match state {
+            Some(s) => {
+                s.drbg.reseed(&seed);
+                s.pid = pid;
+            }
+            None => {
+                *state = Some(State {
+                    drbg: Drbg::new(&seed),
+                    pid,
+                })
+            }
+        });
+        volatile_zero(&mut seed);
+    }
+}
+
+#[allow(unused_unsafe)] // `__cpuid` is safe on newer Rust versions
+fn is_aesni_available() -> bool {
+    #[cfg(not(target_feature = "aes"))]
+    {
+        // SAFETY: All Rust x86 targets are new enough to have CPUID, and we
+        // check that leaf 1 is supported before using it.
+        if unsafe { arch::__cpuid(0) }.eax < 1 {
+            return false;
+        }
+        const AESNI_FLAG: u32 = 1 << 25;
+        if unsafe { arch::__cpuid(1) }.ecx & AESNI_FLAG == 0 {
+            return false;
+        }
+    }
+    true
+}
+
+static AESNI_GOOD: lazy::LazyBool = lazy::LazyBool::new();
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    if !AESNI_GOOD.unsync_init(is_aesni_available) {
+        return Err(Error::NO_AESNI);
+    }
+    for chunk in dest.chunks_mut(MAX_FILL_CHUNK) {
+        // SAFETY: We have already checked that AES-NI is available.
+        unsafe { fill_chunk(chunk) }?;
+    }
+    Ok(())
+}
+
+impl Error {
+    /// AES-NI instructions are not supported by the CPU.
+    // Codes 10 and 11 are used by the `rdrand` seed source.
+    pub(crate) const NO_AESNI: Error = Self::new_internal(12);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encrypt, expand_key, is_aesni_available, Drbg};
+    use core::mem::MaybeUninit;
+
+    fn hex<const N: usize>(s: &str) -> [u8; N] {
+        assert_eq!(s.len(), 2 * N);
+        let mut res = [0; N];
+        for (i, b) in res.iter_mut().enumerate() {
+            *b = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        res
+    }
+
+    fn generate(drbg: &mut Drbg) -> [u8; 64] {
+        let mut buf = [MaybeUninit::new(0u8); 64];
+        unsafe { drbg.generate(&mut buf) };
+        buf.map(|b| unsafe { b.assume_init() })
+    }
+
+    // FIPS 197, Appendix C.3
+    #[test]
+    fn test_aes256() {
+        assert!(is_aesni_available());
+        let key = hex("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f");
+        let block = hex("00112233445566778899aabbccddeeff");
+        let res = unsafe { encrypt(&expand_key(&key), block) };
+        assert_eq!(res, hex("8ea2b7ca516745bfeafc49904b496089"));
+    }
+
+    // NIST CAVP `drbgvectors_pr_false/CTR_DRBG.rsp`, [AES-256 no df], COUNT = 0
+    // (no personalization string or additional input): instantiate, reseed,
+    // generate 512 bits twice and check the second output.
+    #[test]
+    fn test_ctr_drbg() {
+        assert!(is_aesni_available());
+        let entropy = hex("e4bc23c5089a19d86f4119cb3fa08c0a4991e0a1def17e10\
+             1e4c14d9c323460a7c2fb58e0b086c6c57b55f56cae25bad");
+        let entropy_reseed = hex("fd85a836bba85019881e8c6bad23c9061adc75477659acae\
+             a8e4a01dfe07a1832dad1c136f59d70f8653a5dc118663d6");
+        let expected: [u8; 64] = hex(
+            "b2cb8905c05e5950ca31895096be29ea3d5a3b82b269495554eb80fe07de43e1\
+             93b9e7c3ece73b80e062b1c1f68202fbb1c52a040ea2478864295282234aaada",
+        );
+        let mut drbg = unsafe { Drbg::new(&entropy) };
+        unsafe { drbg.reseed(&entropy_reseed) };
+        assert_eq!(drbg.reseed_counter, 1);
+        generate(&mut drbg);
+        assert_eq!(generate(&mut drbg), expected);
+        assert_eq!(drbg.reseed_counter, 3);
+    }
+}
@@ -2,11 +2,12 @@
 use crate::Error;
 use core::{ffi::c_void, mem::MaybeUninit};
 
-pub use crate::util::{inner_u32, inner_u64};
+pub use crate::util::{fill_nonblocking_inner, init_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
 
 pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
     let dst_ptr = dest.as_mut_ptr().cast::<c_void>();
     let ret = unsafe { libc::CCRandomGenerateBytes(dst_ptr, dest.len()) };
+    // Any status other than `kCCSuccess` means that `dest` may not be filled.
     if ret == libc::kCCSuccess {
         Ok(())
     } else {
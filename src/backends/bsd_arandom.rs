@@ -1,8 +1,8 @@
-//! Implementation for NetBSD
+//! Implementation for FreeBSD and NetBSD
 //!
-//! `getrandom(2)` was introduced in NetBSD 10. To support older versions we
-//! implement our own weak linkage to it, and provide a fallback based on the
-//! KERN_ARND sysctl.
+//! `getrandom(2)` was introduced in FreeBSD 12 and NetBSD 10. To support older
+//! versions we implement our own weak linkage to it, and provide a fallback
+//! based on the KERN_ARND sysctl.
 use crate::Error;
 use core::{
     cmp,
@@ -12,11 +12,16 @@ use core::{
     sync::atomic::{AtomicPtr, Ordering},
 };
 
-pub use crate::util::{inner_u32, inner_u64};
+pub use crate::util::{fill_nonblocking_inner, inner_u32, inner_u64};
 
 #[path = "../util_libc.rs"]
 mod util_libc;
 
+/// The `KERN_ARND` polyfill returns at most 256 bytes per call. Since we cannot
+/// know at compile time whether `getrandom(2)` is available, we report the
+/// polyfill's limit.
+pub const MAX_FILL_CHUNK: usize = 256;
+
 unsafe extern "C" fn polyfill_using_kern_arand(
     buf: *mut c_void,
     buflen: libc::size_t,
@@ -27,8 +32,8 @@ unsafe extern "C" fn polyfill_using_kern_arand(
     const MIB_LEN: libc::c_uint = 2;
     static MIB: [libc::c_int; MIB_LEN as usize] = [libc::CTL_KERN, libc::KERN_ARND];
 
-    // NetBSD will only return up to 256 bytes at a time, and
-    // older NetBSD kernels will fail on longer buffers.
+    // FreeBSD and NetBSD will only return up to 256 bytes at a time,
+    // and older NetBSD kernels will fail on longer buffers.
     let mut len = cmp::min(buflen, 256);
     let ret = unsafe { libc::sysctl(MIB.as_ptr(), MIB_LEN, buf, &mut len, ptr::null(), 0) };
 
@@ -49,7 +54,12 @@ fn init() -> *mut c_void {
     static NAME: &[u8] = b"getrandom\0";
     let name_ptr = NAME.as_ptr().cast::<libc::c_char>();
     let mut ptr = unsafe { libc::dlsym(libc::RTLD_DEFAULT, name_ptr) };
-    if ptr.is_null() || cfg!(getrandom_test_netbsd_fallback) {
+    if ptr.is_null()
+        || cfg!(any(
+            getrandom_test_netbsd_fallback,
+            getrandom_test_freebsd_fallback
+        ))
+    {
         // Verify `polyfill_using_kern_arand` has the right signature.
         const POLYFILL: GetRandomFn = polyfill_using_kern_arand;
         ptr = POLYFILL as *mut c_void;
@@ -58,6 +68,13 @@ fn init() -> *mut c_void {
     ptr
 }
 
+pub fn init_inner() -> Result<(), Error> {
+    if GETRANDOM.load(Ordering::Acquire).is_null() {
+        init();
+    }
+    Ok(())
+}
+
 pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
     // Despite being only a single atomic variable, we still cannot always use
     // Ordering::Relaxed, as we need to make sure a successful call to `init`
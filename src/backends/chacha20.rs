@@ -0,0 +1,285 @@
+//! Userspace ChaCha20 generator seeded from the OS
+//!
+//! Output is the ChaCha20 keystream (RFC 8439) under a 256-bit key, taken
+//! from the source selected with the `getrandom_drbg_seed` configuration flag.
+//! Supported values are `"linux_getrandom"`, `"use_file"`, `"rdrand"` and
+//! `"custom"`. After every request the key is replaced with keystream output
+//! which was not returned to the caller ("fast key erasure"), so a later state
+//! compromise does not reveal earlier output.
+//!
+//! The generator is seeded on the first request and reseeded from the same
+//! source every `GETRANDOM_CHACHA20_RESEED_INTERVAL` requests (65536 if the
+//! environment variable was not set at build time). On Linux and Android it is
+//! also reseeded after `fork`, which is detected by comparing process IDs.
+//!
+//! Like `aesni_drbg`, this is an opt-in backend for users who need the
+//! throughput of a userspace generator. The crate is `no_std`, so the state is
+//! shared by all threads and protected by a spin lock instead of being stored
+//! per thread.
+// The seed source may include its own copy of `lazy`
+#![allow(clippy::duplicate_mod)]
+use crate::{
+    util::{parse_decimal, slice_as_uninit, slice_as_uninit_mut, volatile_zero},
+    Error,
+};
+use core::{
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+pub use crate::util::{fill_nonblocking_inner, init_inner, inner_u32, inner_u64};
+
+cfg_if! {
+    if #[cfg(getrandom_drbg_seed = "linux_getrandom")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` is used
+        #[path = "linux_android.rs"]
+        mod seed_source;
+    } else if #[cfg(getrandom_drbg_seed = "use_file")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` is used
+        #[path = "use_file.rs"]
+        mod seed_source;
+    } else if #[cfg(getrandom_drbg_seed = "rdrand")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` is used
+        #[path = "rdrand.rs"]
+        mod seed_source;
+    } else if #[cfg(getrandom_drbg_seed = "custom")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` is used
+        #[path = "custom.rs"]
+        mod seed_source;
+    } else {
+        compile_error!("`chacha20` backend requires a valid `getrandom_drbg_seed` value!");
+    }
+}
+
+/// Maximum number of bytes per request. The key is replaced after each one.
+pub const MAX_FILL_CHUNK: usize = 1 << 16;
+
+/// Number of requests after which the generator is reseeded
+const RESEED_INTERVAL: u64 = match option_env!("GETRANDOM_CHACHA20_RESEED_INTERVAL") {
+    Some(n) => {
+        const ERR: &str = "GETRANDOM_CHACHA20_RESEED_INTERVAL must be a positive decimal u64";
+        let n = parse_decimal(n, ERR);
+        assert!(n > 0, "{}", ERR);
+        n
+    }
+    None => 1 << 16,
+};
+
+const KEY_LEN: usize = 32;
+const BLOCK_LEN: usize = 64;
+
+/// ChaCha20 block function (RFC 8439, Section 2.3)
+fn chacha20_block(key: &[u8; KEY_LEN], counter: u32, nonce: [u32; 3]) -> [u8; BLOCK_LEN] {
+    #[inline(always)]
+    fn quarter_round(s: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+        s[a] = s[a].wrapping_add(s[b]);
+        s[d] = (s[d] ^ s[a]).rotate_left(16);
+        s[c] = s[c].wrapping_add(s[d]);
+        s[b] = (s[b] ^ s[c]).rotate_left(12);
+        s[a] = s[a].wrapping_add(s[b]);
+        s[d] = (s[d] ^ s[a]).rotate_left(8);
+        s[c] = s[c].wrapping_add(s[d]);
+        s[b] = (s[b] ^ s[c]).rotate_left(7);
+    }
+
+    let mut input = [0u32; 16];
+    input[..4].copy_from_slice(&[0x61707865, 0x3320646e, 0x79622d32, 0x6b206574]);
+    for (word, bytes) in input[4..12].iter_mut().zip(key.chunks_exact(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    input[12] = counter;
+    input[13..].copy_from_slice(&nonce);
+
+    let mut s = input;
+    for _ in 0..10 {
+        quarter_round(&mut s, 0, 4, 8, 12);
+        quarter_round(&mut s, 1, 5, 9, 13);
+        quarter_round(&mut s, 2, 6, 10, 14);
+        quarter_round(&mut s, 3, 7, 11, 15);
+        quarter_round(&mut s, 0, 5, 10, 15);
+        quarter_round(&mut s, 1, 6, 11, 12);
+        quarter_round(&mut s, 2, 7, 8, 13);
+        quarter_round(&mut s, 3, 4, 9, 14);
+    }
+
+    let mut block = [0u8; BLOCK_LEN];
+    for ((out, word), init) in block.chunks_exact_mut(4).zip(&s).zip(&input) {
+        out.copy_from_slice(&word.wrapping_add(*init).to_le_bytes());
+    }
+    block
+}
+
+cfg_if! {
+    if #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        not(target_env = ""),
+    ))] {
+        type ProcessId = libc::pid_t;
+
+        fn process_id() -> ProcessId {
+            unsafe { libc::getpid() }
+        }
+    } else {
+        type ProcessId = u8;
+
+        fn process_id() -> ProcessId {
+            0
+        }
+    }
+}
+
+struct Rng {
+    key: [u8; KEY_LEN],
+    /// Number of requests served since the last (re)seed
+    requests: u64,
+    /// ID of the process which last (re)seeded the generator
+    pid: ProcessId,
+}
+
+impl Rng {
+    fn new(seed: &[u8; KEY_LEN], pid: ProcessId) -> Self {
+        Rng {
+            key: *seed,
+            requests: 0,
+            pid,
+        }
+    }
+
+    fn needs_reseed(&self, pid: ProcessId) -> bool {
+        self.pid != pid || self.requests >= RESEED_INTERVAL
+    }
+
+    /// Fill `dest` with keystream blocks 1 and up, then replace the key with
+    /// the start of block 0. `dest` must be at most `MAX_FILL_CHUNK` bytes.
+    fn generate(&mut self, dest: &mut [MaybeUninit<u8>]) {
+        for (counter, chunk) in (1..).zip(dest.chunks_mut(BLOCK_LEN)) {
+            let mut block = chacha20_block(&self.key, counter, [0; 3]);
+            chunk.copy_from_slice(slice_as_uninit(&block[..chunk.len()]));
+            if chunk.len() < BLOCK_LEN {
+                volatile_zero(&mut block);
+            }
+        }
+        let mut block = chacha20_block(&self.key, 0, [0; 3]);
+        self.key.copy_from_slice(&block[..KEY_LEN]);
+        volatile_zero(&mut block);
+        self.requests += 1;
+    }
+}
+
+impl Drop for Rng {
+    fn drop(&mut self) {
+        volatile_zero(&mut self.key);
+    }
+}
+
+/// Spin lock protecting the global state
+struct Lock {
+    locked: AtomicBool,
+    rng: UnsafeCell<Option<Rng>>,
+}
+
+// SAFETY: `rng` is only accessed while holding `locked`.
+unsafe impl Sync for Lock {}
+
+static STATE: Lock = Lock {
+    locked: AtomicBool::new(false),
+    rng: UnsafeCell::new(None),
+};
+
+struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        STATE.locked.store(false, Ordering::Release);
+    }
+}
+
+fn with_rng<T>(f: impl FnOnce(&mut Option<Rng>) -> T) -> T {
+    while STATE
+        .locked
+        .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+        .is_err()
+    {
+        core::hint::spin_loop();
+    }
+    let _guard = Guard;
+    // SAFETY: we hold the lock until `_guard` is dropped.
+    f(unsafe { &mut *STATE.rng.get() })
+}
+
+fn fill_chunk(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    let pid = process_id();
+    loop {
+        let done = with_rng(|rng| match rng {
+            Some(rng) if !rng.needs_reseed(pid) => {
+                rng.generate(dest);
+                true
+            }
+            _ => false,
+        });
+        if done {
+            return Ok(());
+        }
+        // The seed source may block, so it is called without holding the lock.
+        let mut seed = [0u8; KEY_LEN];
+        // SAFETY: `fill_inner` never writes uninitialized bytes.
+        seed_source::fill_inner(unsafe { slice_as_uninit_mut(&mut seed) })?;
+        // The previous generator, if any, is zeroized when dropped here.
+        with_rng(|rng| *rng = Some(Rng::new(&seed, pid)));
+        volatile_zero(&mut seed);
+    }
+}
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    for chunk in dest.chunks_mut(MAX_FILL_CHUNK) {
+        fill_chunk(chunk)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex<const N: usize>(s: &str) -> [u8; N] {
+        let mut res = [0u8; N];
+        for (i, byte) in res.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        res
+    }
+
+    #[test]
+    fn test_chacha20_block() {
+        // RFC 8439, Section 2.3.2
+        let key = hex("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f");
+        let nonce = [0x09000000, 0x4a000000, 0x00000000];
+        let expected: [u8; 64] = hex(
+            "10f1e7e4d13b5915500fdd1fa32071c4c7d1f4c733c068030422aa9ac3d46c4e\
+             d2826446079faa0914c2d705d98b02a2b5129cd1de164eb9cbd083e8a2503c4e",
+        );
+        assert_eq!(chacha20_block(&key, 1, nonce), expected);
+    }
+
+    #[test]
+    fn test_generate() {
+        // RFC 8439, Appendix A.1, Test Vectors #1 and #2
+        let block_0: [u8; 64] = hex(
+            "76b8e0ada0f13d90405d6ae55386bd28bdd219b8a08ded1aa836efcc8b770dc7\
+             da41597c5157488d7724e03fb8d84a376a43b8f41518a11cc387b669b2ee6586",
+        );
+        let block_1: [u8; 64] = hex(
+            "9f07e7be5551387a98ba977c732d080dcb0f29a048e3656912c6533e32ee7aed\
+             29b721769ce64e43d57133b074d839d531ed1f28510afb45ace10a1f4b794d6f",
+        );
+        let mut rng = Rng::new(&[0; KEY_LEN], 0);
+        let mut buf = [MaybeUninit::uninit(); 40];
+        rng.generate(&mut buf);
+        // SAFETY: `generate` initialized all of `buf`.
+        let buf: [u8; 40] = unsafe { core::mem::transmute(buf) };
+        assert_eq!(buf, block_1[..40]);
+        assert_eq!(rng.key, block_0[..KEY_LEN]);
+        assert_eq!(rng.requests, 1);
+    }
+}
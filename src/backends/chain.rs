@@ -0,0 +1,95 @@
+//! Backend which tries several sources in order until one of them succeeds
+//!
+//! The sources are listed in order in the comma-separated
+//! `getrandom_backend_order` configuration flag, e.g.
+//! `"linux_getrandom,use_file,rdrand"`. Supported sources are
+//! `"linux_getrandom"`, `"use_file"`, `"rdrand"`, `"rndr"` and `"custom"`, and
+//! each of them can be listed only once.
+//!
+//! Note that all of these sources are uncapped (see `MAX_FILL_CHUNK`).
+// `linux_getrandom` and `use_file` include their own copies of `util_libc`
+#![allow(clippy::duplicate_mod)]
+use crate::Error;
+use core::mem::MaybeUninit;
+
+pub use crate::util::{fill_nonblocking_inner, init_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
+
+#[cfg(getrandom_chain_source = "custom")]
+#[allow(dead_code, unused_imports)] // only `fill_inner` is used
+#[path = "custom.rs"]
+mod custom;
+#[cfg(getrandom_chain_source = "linux_getrandom")]
+#[allow(dead_code, unused_imports)] // only `fill_inner` is used
+#[path = "linux_android.rs"]
+mod linux_getrandom;
+#[cfg(getrandom_chain_source = "rdrand")]
+#[allow(dead_code, unused_imports)] // only `fill_inner` is used
+#[path = "rdrand.rs"]
+mod rdrand;
+#[cfg(getrandom_chain_source = "rndr")]
+#[allow(dead_code, unused_imports)] // only `fill_inner` is used
+#[path = "rndr.rs"]
+mod rndr;
+#[cfg(getrandom_chain_source = "use_file")]
+#[allow(dead_code, unused_imports)] // only `fill_inner` is used
+#[path = "use_file.rs"]
+mod use_file;
+
+type FillFn = fn(&mut [MaybeUninit<u8>]) -> Result<(), Error>;
+
+// Defines `SOURCES`, the `fill_inner` functions of the sources listed in
+// `getrandom_backend_order`, see `build.rs`.
+include!(concat!(env!("OUT_DIR"), "/chain_sources.rs"));
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    fill_chain(dest, SOURCES)
+}
+
+/// Try `sources` in order and return the error of the last one if all of them fail.
+fn fill_chain(dest: &mut [MaybeUninit<u8>], sources: &[FillFn]) -> Result<(), Error> {
+    let mut res = Err(Error::UNEXPECTED);
+    for fill in sources {
+        res = fill(dest);
+        if res.is_ok() {
+            break;
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fill_chain, Error, FillFn, MaybeUninit};
+
+    fn fill_err(_: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+        Err(Error::UNSUPPORTED)
+    }
+
+    fn fill_ones(buf: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+        for b in buf.iter_mut() {
+            b.write(1);
+        }
+        Ok(())
+    }
+
+    fn fill_twos(buf: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+        for b in buf.iter_mut() {
+            b.write(2);
+        }
+        Ok(())
+    }
+
+    fn chain(sources: &[FillFn]) -> Result<[u8; 8], Error> {
+        let mut buf = [MaybeUninit::new(0); 8];
+        fill_chain(&mut buf, sources)?;
+        Ok(buf.map(|b| unsafe { b.assume_init() }))
+    }
+
+    #[test]
+    fn test_chain() {
+        assert_eq!(chain(&[fill_ones, fill_twos]), Ok([1; 8]));
+        assert_eq!(chain(&[fill_err, fill_twos]), Ok([2; 8]));
+        assert_eq!(chain(&[fill_err, fill_err, fill_ones]), Ok([1; 8]));
+        assert_eq!(chain(&[fill_err, fill_err]), Err(Error::UNSUPPORTED));
+    }
+}
@@ -0,0 +1,160 @@
+//! Backend which XORs the output of two independent sources
+//!
+//! The sources are selected with the `getrandom_combine_a` and
+//! `getrandom_combine_b` configuration flags. Supported values are
+//! `"linux_getrandom"`, `"rdrand"`, `"rndr"` and `"custom"`.
+use crate::{util::volatile_zero, Error};
+use core::mem::MaybeUninit;
+
+pub use crate::util::{fill_nonblocking_inner, inner_u32, inner_u64};
+
+cfg_if! {
+    if #[cfg(getrandom_combine_a = "linux_getrandom")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` and `MAX_FILL_CHUNK` are used
+        #[path = "linux_android.rs"]
+        mod source_a;
+    } else if #[cfg(getrandom_combine_a = "rdrand")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` and `MAX_FILL_CHUNK` are used
+        #[path = "rdrand.rs"]
+        mod source_a;
+    } else if #[cfg(getrandom_combine_a = "rndr")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` and `MAX_FILL_CHUNK` are used
+        #[path = "rndr.rs"]
+        mod source_a;
+    } else if #[cfg(getrandom_combine_a = "custom")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` and `MAX_FILL_CHUNK` are used
+        #[path = "custom.rs"]
+        mod source_a;
+    } else {
+        compile_error!("`combined` backend requires a valid `getrandom_combine_a` value!");
+    }
+}
+
+cfg_if! {
+    if #[cfg(any(
+        all(getrandom_combine_a = "linux_getrandom", getrandom_combine_b = "linux_getrandom"),
+        all(getrandom_combine_a = "rdrand", getrandom_combine_b = "rdrand"),
+        all(getrandom_combine_a = "rndr", getrandom_combine_b = "rndr"),
+        all(getrandom_combine_a = "custom", getrandom_combine_b = "custom"),
+    ))] {
+        compile_error!("`combined` backend requires two different sources!");
+        // Avoid follow-up errors caused by including the same source twice
+        use source_a as source_b;
+    } else if #[cfg(getrandom_combine_b = "linux_getrandom")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` and `MAX_FILL_CHUNK` are used
+        #[path = "linux_android.rs"]
+        mod source_b;
+    } else if #[cfg(getrandom_combine_b = "rdrand")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` and `MAX_FILL_CHUNK` are used
+        #[path = "rdrand.rs"]
+        mod source_b;
+    } else if #[cfg(getrandom_combine_b = "rndr")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` and `MAX_FILL_CHUNK` are used
+        #[path = "rndr.rs"]
+        mod source_b;
+    } else if #[cfg(getrandom_combine_b = "custom")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` and `MAX_FILL_CHUNK` are used
+        #[path = "custom.rs"]
+        mod source_b;
+    } else {
+        compile_error!("`combined` backend requires a valid `getrandom_combine_b` value!");
+    }
+}
+
+/// Size of the stack buffer used for the output of the second source
+const SCRATCH_LEN: usize = 256;
+
+// The second source is always called with at most `SCRATCH_LEN` bytes
+#[allow(clippy::absurd_extreme_comparisons)] // the limit is usually `usize::MAX`
+const _: () = assert!(SCRATCH_LEN <= source_b::MAX_FILL_CHUNK);
+
+/// Requests of up to this length are served with a single call to each source.
+pub const MAX_FILL_CHUNK: usize = min(source_a::MAX_FILL_CHUNK, SCRATCH_LEN);
+
+const fn min(a: usize, b: usize) -> usize {
+    if a < b {
+        a
+    } else {
+        b
+    }
+}
+
+pub fn init_inner() -> Result<(), Error> {
+    source_a::init_inner()?;
+    source_b::init_inner()
+}
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    combine(dest, source_a::fill_inner, source_b::fill_inner)
+}
+
+fn combine(
+    dest: &mut [MaybeUninit<u8>],
+    fill_a: impl Fn(&mut [MaybeUninit<u8>]) -> Result<(), Error>,
+    fill_b: impl Fn(&mut [MaybeUninit<u8>]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    fill_a(dest)?;
+    let mut scratch = [0u8; SCRATCH_LEN];
+    let mut res = Ok(());
+    for chunk in dest.chunks_mut(SCRATCH_LEN) {
+        let buf = &mut scratch[..chunk.len()];
+        // SAFETY: `MaybeUninit<u8>` is layout-compatible with `u8` and `buf`
+        // is initialized, so the sources can only write initialized bytes.
+        let uninit_buf = unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) };
+        res = fill_b(uninit_buf);
+        if res.is_err() {
+            break;
+        }
+        for (d, s) in chunk.iter_mut().zip(buf.iter()) {
+            // SAFETY: `dest` was initialized by `fill_a`
+            let d_val = unsafe { d.assume_init() };
+            d.write(d_val ^ s);
+        }
+    }
+    // Do not leave the output of the second source on the stack
+    volatile_zero(&mut scratch);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{combine, Error, MaybeUninit};
+
+    fn fill_counter(buf: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+        for (i, b) in buf.iter_mut().enumerate() {
+            b.write(u8::try_from(i % 256).unwrap());
+        }
+        Ok(())
+    }
+
+    fn fill_const(buf: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+        for b in buf.iter_mut() {
+            b.write(0x5A);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_combine_xor() {
+        let mut buf = [MaybeUninit::uninit(); 1000];
+        combine(&mut buf, fill_counter, fill_const).unwrap();
+        for (i, b) in buf.iter().enumerate() {
+            let expected = u8::try_from(i % 256).unwrap() ^ 0x5A;
+            assert_eq!(unsafe { b.assume_init() }, expected);
+        }
+    }
+
+    #[test]
+    fn test_combine_error() {
+        let fill_err = |_: &mut [MaybeUninit<u8>]| Err(Error::UNEXPECTED);
+        let mut buf = [MaybeUninit::uninit(); 100];
+        assert_eq!(
+            combine(&mut buf, fill_counter, fill_err),
+            Err(Error::UNEXPECTED)
+        );
+        assert_eq!(
+            combine(&mut buf, fill_err, fill_const),
+            Err(Error::UNEXPECTED)
+        );
+    }
+}
@@ -0,0 +1,50 @@
+//! Counter backend for deterministic tests
+//!
+//! WARNING: this backend is NOT random! It fills buffers with an incrementing
+//! (wrapping) byte counter, i.e. `dest[i] = start + i`, where `start` is
+//! advanced by every call and can be set with [`reset_counter`].
+//! It MUST NOT be used outside of tests.
+use crate::{util::fill_counter, Error};
+use core::{
+    mem::MaybeUninit,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+pub use crate::util::{fill_nonblocking_inner, init_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
+
+static COUNTER: AtomicU8 = AtomicU8::new(0);
+
+/// Set the value of the first byte produced by the next call.
+///
+/// Only available with the `counter` backend.
+pub fn reset_counter(start: u8) {
+    COUNTER.store(start, Ordering::Relaxed);
+}
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    fill_counter(&COUNTER, dest);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::reset_counter;
+
+    #[test]
+    fn test_counter() {
+        reset_counter(250);
+        let mut buf = [0u8; 4];
+        crate::fill(&mut buf).unwrap();
+        assert_eq!(buf, [250, 251, 252, 253]);
+        crate::fill(&mut buf).unwrap();
+        assert_eq!(buf, [254, 255, 0, 1]);
+
+        reset_counter(7);
+        let mut buf = [0u8; 300];
+        crate::fill(&mut buf).unwrap();
+        assert_eq!(buf[0], 7);
+        assert_eq!(buf[299], 50);
+        crate::fill(&mut buf[..1]).unwrap();
+        assert_eq!(buf[0], 51);
+    }
+}
@@ -2,11 +2,26 @@
 use crate::Error;
 use core::mem::MaybeUninit;
 
-pub use crate::util::{inner_u32, inner_u64};
+pub use crate::util::{fill_nonblocking_inner, init_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
 
 pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
     extern "Rust" {
         fn __getrandom_v03_custom(dest: *mut u8, len: usize) -> Result<(), Error>;
     }
-    unsafe { __getrandom_v03_custom(dest.as_mut_ptr().cast(), dest.len()) }
+    let res = unsafe { __getrandom_v03_custom(dest.as_mut_ptr().cast(), dest.len()) };
+
+    // `fill_uninit` unpoisons `dest` after a successful call, which would hide
+    // a custom implementation that does not initialize all of it. So check
+    // with MemorySanitizer that it did so before returning.
+    #[cfg(all(getrandom_msan, debug_assertions))]
+    {
+        extern "C" {
+            fn __msan_check_mem_is_initialized(x: *const core::ffi::c_void, size: usize);
+        }
+        if res.is_ok() {
+            unsafe { __msan_check_mem_is_initialized(dest.as_ptr().cast(), dest.len()) };
+        }
+    }
+
+    res
 }
@@ -0,0 +1,129 @@
+//! Deterministic backend for reproducible fuzzing
+//!
+//! WARNING: this backend is NOT secure! It produces a fixed stream of bytes
+//! generated by Xoshiro128++ seeded with the value of the `GETRANDOM_FUZZ_SEED`
+//! environment variable at build time (or 0 if it was not set). Repeated
+//! runs of a program which makes the same sequence of calls get identical
+//! output. It MUST NOT be used outside of fuzzing and testing.
+use crate::{
+    util::{parse_decimal, slice_as_uninit},
+    Error,
+};
+use core::{
+    cell::UnsafeCell,
+    mem::{size_of, MaybeUninit},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+pub use crate::util::{fill_nonblocking_inner, init_inner, MAX_FILL_CHUNK};
+
+const SEED: u64 = match option_env!("GETRANDOM_FUZZ_SEED") {
+    Some(seed) => parse_decimal(seed, "GETRANDOM_FUZZ_SEED must be a decimal u64"),
+    None => 0,
+};
+
+/// Xoshiro128++ generator, the same one used by the `custom` backend tests
+struct Xoshiro128PlusPlus {
+    s: [u32; 4],
+}
+
+impl Xoshiro128PlusPlus {
+    /// Expands `seed` into the initial state with SplitMix64, so that
+    /// the state is never all zeros.
+    const fn new(mut seed: u64) -> Self {
+        const PHI: u64 = 0x9e3779b97f4a7c15;
+        let mut s = [0u32; 4];
+        let mut i = 0;
+        while i < s.len() {
+            seed = seed.wrapping_add(PHI);
+            let mut z = seed;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z = z ^ (z >> 31);
+            // `truncate` is not usable in `const fn`
+            #[allow(clippy::cast_possible_truncation)]
+            let val = z as u32;
+            s[i] = val;
+            i += 1;
+        }
+        Self { s }
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        let res = self.s[0]
+            .wrapping_add(self.s[3])
+            .rotate_left(7)
+            .wrapping_add(self.s[0]);
+
+        let t = self.s[1] << 9;
+
+        self.s[2] ^= self.s[0];
+        self.s[3] ^= self.s[1];
+        self.s[1] ^= self.s[2];
+        self.s[0] ^= self.s[3];
+
+        self.s[2] ^= t;
+
+        self.s[3] = self.s[3].rotate_left(11);
+
+        res
+    }
+}
+
+/// Spin lock protecting the generator state
+struct Lock {
+    locked: AtomicBool,
+    rng: UnsafeCell<Xoshiro128PlusPlus>,
+}
+
+// SAFETY: `rng` is only accessed while holding `locked`.
+unsafe impl Sync for Lock {}
+
+static STATE: Lock = Lock {
+    locked: AtomicBool::new(false),
+    rng: UnsafeCell::new(Xoshiro128PlusPlus::new(SEED)),
+};
+
+struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        STATE.locked.store(false, Ordering::Release);
+    }
+}
+
+fn with_rng<T>(f: impl FnOnce(&mut Xoshiro128PlusPlus) -> T) -> T {
+    while STATE
+        .locked
+        .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+        .is_err()
+    {
+        core::hint::spin_loop();
+    }
+    let _guard = Guard;
+    // SAFETY: we hold the lock until `_guard` is dropped.
+    f(unsafe { &mut *STATE.rng.get() })
+}
+
+pub fn inner_u32() -> Result<u32, Error> {
+    Ok(with_rng(Xoshiro128PlusPlus::next_u32))
+}
+
+pub fn inner_u64() -> Result<u64, Error> {
+    Ok(with_rng(|rng| {
+        let lo = rng.next_u32();
+        let hi = rng.next_u32();
+        (u64::from(hi) << 32) | u64::from(lo)
+    }))
+}
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    with_rng(|rng| {
+        // Little-endian bytes make the output identical across targets
+        for chunk in dest.chunks_mut(size_of::<u32>()) {
+            let src = rng.next_u32().to_le_bytes();
+            chunk.copy_from_slice(slice_as_uninit(&src[..chunk.len()]));
+        }
+    });
+    Ok(())
+}
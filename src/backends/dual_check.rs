@@ -0,0 +1,185 @@
+//! Backend which runs a repetition health test on the output of another source
+//!
+//! The source is selected with the `getrandom_dual_check_source` configuration
+//! flag. Supported values are `"linux_getrandom"`, `"rdrand"`, `"rndr"` and
+//! `"custom"`.
+//!
+//! This is the NIST SP 800-90B repetition count test with a cutoff of 2, i.e.
+//! any repeated sample fails the request with [`Error::STUCK_SOURCE`]:
+//! - Requests shorter than two samples (including `u32` and `u64`) read two
+//!   values of the request's length (but at least one sample long) with
+//!   separate calls to the source, compare them and return (a prefix of)
+//!   the first one.
+//! - Longer requests are filled with a single call to the source and every
+//!   sample is compared with the previous one.
+//!
+//! Samples are 8 bytes long, so a working source fails the test with
+//! negligible probability.
+use crate::{util::volatile_zero, Error};
+use core::mem::MaybeUninit;
+
+pub use crate::util::{fill_nonblocking_inner, inner_u32, inner_u64};
+
+cfg_if! {
+    if #[cfg(getrandom_dual_check_source = "linux_getrandom")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` and `MAX_FILL_CHUNK` are used
+        #[path = "linux_android.rs"]
+        mod source;
+    } else if #[cfg(getrandom_dual_check_source = "rdrand")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` and `MAX_FILL_CHUNK` are used
+        #[path = "rdrand.rs"]
+        mod source;
+    } else if #[cfg(getrandom_dual_check_source = "rndr")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` and `MAX_FILL_CHUNK` are used
+        #[path = "rndr.rs"]
+        mod source;
+    } else if #[cfg(getrandom_dual_check_source = "custom")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` and `MAX_FILL_CHUNK` are used
+        #[path = "custom.rs"]
+        mod source;
+    } else {
+        compile_error!(
+            "`dual_check` backend requires a valid `getrandom_dual_check_source` value!"
+        );
+    }
+}
+
+pub use source::MAX_FILL_CHUNK;
+
+/// Length of a single sample compared by the health test
+const SAMPLE_LEN: usize = 8;
+
+pub fn init_inner() -> Result<(), Error> {
+    source::init_inner()
+}
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    dual_check(dest, source::fill_inner)
+}
+
+fn dual_check(
+    dest: &mut [MaybeUninit<u8>],
+    fill: impl Fn(&mut [MaybeUninit<u8>]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    if dest.len() < 2 * SAMPLE_LEN {
+        return check_small(dest, fill);
+    }
+    fill(dest)?;
+    // SAFETY: `dest` was initialized by `fill`
+    let buf = unsafe { &*(dest as *const [MaybeUninit<u8>] as *const [u8]) };
+    let mut samples = buf.chunks_exact(SAMPLE_LEN);
+    let mut prev = samples.next();
+    for sample in samples {
+        if prev == Some(sample) {
+            return Err(Error::STUCK_SOURCE);
+        }
+        prev = Some(sample);
+    }
+    Ok(())
+}
+
+fn check_small(
+    dest: &mut [MaybeUninit<u8>],
+    fill: impl Fn(&mut [MaybeUninit<u8>]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let len = dest.len().max(SAMPLE_LEN);
+    let mut scratch = [[0u8; 2 * SAMPLE_LEN]; 2];
+    let mut res = Ok(());
+    for buf in scratch.iter_mut() {
+        let buf = &mut buf[..len];
+        // SAFETY: `MaybeUninit<u8>` is layout-compatible with `u8` and `buf`
+        // is initialized, so the source can only write initialized bytes.
+        let uninit_buf = unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) };
+        res = fill(uninit_buf);
+        if res.is_err() {
+            break;
+        }
+    }
+    if res.is_ok() {
+        let [first, second] = &scratch;
+        if first[..len] == second[..len] {
+            res = Err(Error::STUCK_SOURCE);
+        } else {
+            for (d, s) in dest.iter_mut().zip(first.iter()) {
+                d.write(*s);
+            }
+        }
+    }
+    // Do not leave the samples on the stack
+    for buf in scratch.iter_mut() {
+        volatile_zero(buf);
+    }
+    res
+}
+
+impl Error {
+    /// The source returned the same sample twice in a row.
+    // Codes 10 and 11 are used by the `rdrand` and `rndr` sources.
+    pub(crate) const STUCK_SOURCE: Error = Self::new_internal(12);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dual_check, Error, MaybeUninit, SAMPLE_LEN};
+    use core::sync::atomic::{AtomicU64, Ordering::Relaxed};
+
+    /// Fills each sample with a distinct value
+    fn fill_distinct(buf: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        for chunk in buf.chunks_mut(SAMPLE_LEN) {
+            let val = COUNTER.fetch_add(1, Relaxed).to_le_bytes();
+            for (d, s) in chunk.iter_mut().zip(val.iter()) {
+                d.write(*s);
+            }
+        }
+        Ok(())
+    }
+
+    /// Stuck source which always returns the same byte
+    fn fill_const(buf: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+        for b in buf.iter_mut() {
+            b.write(0x5A);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_dual_check_ok() {
+        for len in [1, 4, 8, 15, 16, 17, 1000] {
+            let mut buf = [MaybeUninit::uninit(); 1000];
+            dual_check(&mut buf[..len], fill_distinct).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_dual_check_stuck() {
+        for len in [1, 4, 8, 15, 16, 17, 1000] {
+            let mut buf = [MaybeUninit::uninit(); 1000];
+            assert_eq!(
+                dual_check(&mut buf[..len], fill_const),
+                Err(Error::STUCK_SOURCE)
+            );
+        }
+    }
+
+    #[test]
+    fn test_dual_check_repeated_sample() {
+        // Only the last two samples of a bulk request are equal
+        let fill = |buf: &mut [MaybeUninit<u8>]| {
+            fill_distinct(buf)?;
+            let last = buf.len() - SAMPLE_LEN;
+            buf.copy_within(last - SAMPLE_LEN..last, last);
+            Ok(())
+        };
+        let mut buf = [MaybeUninit::uninit(); 64];
+        assert_eq!(dual_check(&mut buf, fill), Err(Error::STUCK_SOURCE));
+    }
+
+    #[test]
+    fn test_dual_check_error() {
+        let fill_err = |_: &mut [MaybeUninit<u8>]| Err(Error::UNEXPECTED);
+        let mut buf = [MaybeUninit::uninit(); 100];
+        assert_eq!(dual_check(&mut buf, fill_err), Err(Error::UNEXPECTED));
+        assert_eq!(dual_check(&mut buf[..4], fill_err), Err(Error::UNEXPECTED));
+    }
+}
@@ -0,0 +1,99 @@
+//! Implementation for x86 UEFI targets using EFI_RNG_PROTOCOL with a RDRAND fallback
+//!
+//! RDRAND is used only if the firmware does not provide a working RNG
+//! protocol handle (i.e. `efi_rng` fails with [`Error::NO_RNG_HANDLE`]).
+//! All other errors of the RNG protocol are returned as is. Once the
+//! fallback is engaged, the protocol is not looked up again.
+use crate::Error;
+use core::{
+    mem::MaybeUninit,
+    sync::atomic::{AtomicBool, Ordering::Relaxed},
+};
+
+pub use crate::util::{fill_nonblocking_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
+
+#[allow(dead_code, unused_imports)] // only `fill_inner` and `init_inner` are used
+#[path = "efi_rng.rs"]
+mod efi_rng;
+
+#[allow(dead_code, unused_imports)] // only `fill_inner` and `init_inner` are used
+#[path = "rdrand.rs"]
+mod rdrand;
+
+/// Set once the firmware was found to lack a working RNG protocol handle.
+static USE_RDRAND: AtomicBool = AtomicBool::new(false);
+
+/// Run `efi`, falling back to `rdrand` (now and for all following calls)
+/// if no RNG protocol handle is available.
+fn with_fallback(
+    use_rdrand: &AtomicBool,
+    dest: &mut [MaybeUninit<u8>],
+    efi: impl FnOnce(&mut [MaybeUninit<u8>]) -> Result<(), Error>,
+    rdrand: impl FnOnce(&mut [MaybeUninit<u8>]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    if !use_rdrand.load(Relaxed) {
+        match efi(dest) {
+            Err(Error::NO_RNG_HANDLE) => use_rdrand.store(true, Relaxed),
+            res => return res,
+        }
+    }
+    rdrand(dest)
+}
+
+pub fn init_inner() -> Result<(), Error> {
+    with_fallback(
+        &USE_RDRAND,
+        &mut [],
+        |_| efi_rng::init_inner(),
+        |_| rdrand::init_inner(),
+    )
+}
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    with_fallback(&USE_RDRAND, dest, efi_rng::fill_inner, rdrand::fill_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{with_fallback, AtomicBool, Error, MaybeUninit};
+    use core::cell::Cell;
+
+    fn fill_ones(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+        for b in dest {
+            b.write(1);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_rng_handle() {
+        let use_rdrand = AtomicBool::new(false);
+        let efi_calls = Cell::new(0);
+        let efi = |_: &mut [MaybeUninit<u8>]| {
+            efi_calls.set(efi_calls.get() + 1);
+            Err(Error::NO_RNG_HANDLE)
+        };
+        let mut buf = [MaybeUninit::uninit(); 4];
+        assert_eq!(with_fallback(&use_rdrand, &mut buf, efi, fill_ones), Ok(()));
+        assert!(buf.iter().all(|b| unsafe { b.assume_init() } == 1));
+        // The protocol is not looked up again
+        assert_eq!(with_fallback(&use_rdrand, &mut buf, efi, fill_ones), Ok(()));
+        assert_eq!(efi_calls.get(), 1);
+    }
+
+    #[test]
+    fn test_protocol_errors() {
+        let use_rdrand = AtomicBool::new(false);
+        let rdrand =
+            |_: &mut [MaybeUninit<u8>]| -> Result<(), Error> { panic!("RDRAND must not be used") };
+        let mut buf = [MaybeUninit::uninit(); 4];
+        assert_eq!(
+            with_fallback(&use_rdrand, &mut buf, fill_ones, rdrand),
+            Ok(())
+        );
+        for err in [Error::BOOT_SERVICES_UNAVAILABLE, Error::UNEXPECTED] {
+            let efi = |_: &mut [MaybeUninit<u8>]| Err(err);
+            assert_eq!(with_fallback(&use_rdrand, &mut buf, efi, rdrand), Err(err));
+        }
+    }
+}
@@ -2,7 +2,7 @@
 use crate::Error;
 use core::{ffi::c_void, mem::MaybeUninit};
 
-pub use crate::util::{inner_u32, inner_u64};
+pub use crate::util::{fill_nonblocking_inner, init_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
 
 extern "C" {
     fn esp_fill_random(buf: *mut c_void, len: usize) -> u32;
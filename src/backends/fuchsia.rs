@@ -1,15 +1,15 @@
 //! Implementation for Fuchsia Zircon
-use crate::Error;
+use crate::{zircon, Error};
 use core::mem::MaybeUninit;
 
-pub use crate::util::{inner_u32, inner_u64};
+pub use crate::util::{fill_nonblocking_inner, init_inner, inner_u32, inner_u64};
 
-#[link(name = "zircon")]
-extern "C" {
-    fn zx_cprng_draw(buffer: *mut u8, length: usize);
-}
+#[cfg(not(target_os = "fuchsia"))]
+compile_error!("`fuchsia` backend can be enabled only for Fuchsia targets!");
+
+pub const MAX_FILL_CHUNK: usize = zircon::CPRNG_DRAW_MAX_LEN;
 
 pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
-    unsafe { zx_cprng_draw(dest.as_mut_ptr().cast::<u8>(), dest.len()) }
+    zircon::cprng_draw(dest);
     Ok(())
 }
@@ -5,18 +5,46 @@
 //!   - OpenBSD 5.6
 //!   - Emscripten 2.0.5
 //!   - vita newlib since Dec 2021
+//!   - iOS 10, tvOS 10, watchOS 3, visionOS 1 (with `getrandom_apple_getentropy`)
+//!   - FreeBSD 12 (with `getrandom_freebsd_getentropy`)
 //!
-//! For these targets, we use getentropy(2) because getrandom(2) doesn't exist.
+//! For most of these targets, we use getentropy(2) because getrandom(2)
+//! doesn't exist. On FreeBSD, getentropy(3) is permitted in Capsicum
+//! capability mode, so it can be selected instead of the default backend.
+//!
+//! On OpenBSD, `u32` and `u64` use arc4random(3) instead, which avoids the
+//! system call for small requests.
 use crate::Error;
 use core::{ffi::c_void, mem::MaybeUninit};
 
-pub use crate::util::{inner_u32, inner_u64};
+cfg_if! {
+    if #[cfg(target_os = "openbsd")] {
+        // arc4random(3) is a ChaCha20-based CSPRNG seeded and regularly
+        // reseeded by the kernel. It never fails.
+        pub fn inner_u32() -> Result<u32, Error> {
+            Ok(unsafe { libc::arc4random() })
+        }
+
+        pub fn inner_u64() -> Result<u64, Error> {
+            let a = unsafe { libc::arc4random() };
+            let b = unsafe { libc::arc4random() };
+            Ok((u64::from(a) << 32) | u64::from(b))
+        }
+    } else {
+        pub use crate::util::{inner_u32, inner_u64};
+    }
+}
 
 #[path = "../util_libc.rs"]
 mod util_libc;
 
+pub use crate::util::{fill_nonblocking_inner, init_inner};
+
+/// `getentropy(2)` fails with `EIO` for requests longer than 256 bytes.
+pub const MAX_FILL_CHUNK: usize = 256;
+
 pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
-    for chunk in dest.chunks_mut(256) {
+    for chunk in dest.chunks_mut(MAX_FILL_CHUNK) {
         let ret = unsafe { libc::getentropy(chunk.as_mut_ptr().cast::<c_void>(), chunk.len()) };
         if ret != 0 {
             return Err(util_libc::last_os_error());
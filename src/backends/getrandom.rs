@@ -3,22 +3,23 @@
 //! Available since:
 //!   - Linux Kernel 3.17, Glibc 2.25, Musl 1.1.20
 //!   - Android API level 23 (Marshmallow)
-//!   - NetBSD 10.0
-//!   - FreeBSD 12.0
 //!   - illumos since Dec 2018
 //!   - DragonFly 5.7
-//!   - Hurd Glibc 2.31
 //!   - shim-3ds since Feb 2022
 //!
 //! For these platforms, we always use the default pool and never set the
-//! GRND_RANDOM flag to use the /dev/random pool. On Linux/Android/Hurd, using
-//! GRND_RANDOM is not recommended. On NetBSD/FreeBSD/Dragonfly/3ds, it does
-//! nothing. On illumos, the default pool is used to implement getentropy(2),
-//! so we assume it is acceptable here.
+//! GRND_RANDOM flag to use the /dev/random pool. On Linux/Android, using
+//! GRND_RANDOM is not recommended. On Dragonfly/3ds, it does nothing. On
+//! illumos, the default pool is used to implement getentropy(2), so we
+//! assume it is acceptable here.
+//!
+//! None of these platforms guarantee that a single call fills the whole
+//! buffer, so `sys_fill_exact` keeps calling getrandom(2) on the unfilled
+//! tail until the whole buffer is filled.
 use crate::Error;
 use core::{ffi::c_void, mem::MaybeUninit};
 
-pub use crate::util::{inner_u32, inner_u64};
+pub use crate::util::{fill_nonblocking_inner, init_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
 
 #[path = "../util_libc.rs"]
 mod util_libc;
@@ -1,7 +1,9 @@
 //! Implementation for Hermit
-use crate::Error;
+use crate::{util::skip_filled, Error};
 use core::mem::MaybeUninit;
 
+pub use crate::util::{fill_nonblocking_inner, init_inner, MAX_FILL_CHUNK};
+
 extern "C" {
     fn sys_read_entropy(buffer: *mut u8, length: usize, flags: u32) -> isize;
     // Note that `sys_secure_rand32/64` are implemented using `sys_read_entropy`:
@@ -38,7 +40,7 @@ pub fn fill_inner(mut dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
         match res {
             res if res > 0 => {
                 let len = usize::try_from(res).map_err(|_| Error::UNEXPECTED)?;
-                dest = dest.get_mut(len..).ok_or(Error::UNEXPECTED)?;
+                dest = skip_filled(dest, len)?;
             }
             code => {
                 let err = u32::try_from(code.unsigned_abs())
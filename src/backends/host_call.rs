@@ -0,0 +1,45 @@
+//! Implementation using a function imported from the Wasm host
+//!
+//! The host must provide the following import (shown in WAT):
+//! ```wat
+//! (import "getrandom" "host_getrandom" (func (param i32 i32) (result i32)))
+//! ```
+//! i.e. `fn host_getrandom(ptr: *mut u8, len: usize) -> i32`, which fills
+//! `len` bytes starting at `ptr` in the linear memory of the module with
+//! random bytes and returns 0 on success. A positive return value is
+//! interpreted as an OS error code, any other value results in
+//! [`Error::UNEXPECTED`].
+//!
+//! The import module name is `getrandom` by default. It can be changed to
+//! `env` with `--cfg getrandom_host_call_module="env"`.
+use crate::Error;
+use core::mem::MaybeUninit;
+
+pub use crate::util::{fill_nonblocking_inner, init_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
+
+#[cfg(not(target_family = "wasm"))]
+compile_error!("`host_call` backend can be enabled only for Wasm targets!");
+
+// `wasm_import_module` requires a string literal, so the supported module
+// names have to be listed explicitly.
+#[cfg_attr(
+    not(getrandom_host_call_module = "env"),
+    link(wasm_import_module = "getrandom")
+)]
+#[cfg_attr(getrandom_host_call_module = "env", link(wasm_import_module = "env"))]
+extern "C" {
+    fn host_getrandom(ptr: *mut u8, len: usize) -> i32;
+}
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    let ret = unsafe { host_getrandom(dest.as_mut_ptr().cast::<u8>(), dest.len()) };
+    match ret {
+        0 => Ok(()),
+        code => {
+            let err = u32::try_from(code)
+                .map(Error::from_os_error)
+                .unwrap_or(Error::UNEXPECTED);
+            Err(err)
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! Implementation for GNU/Hurd with `/dev/urandom` fallback
+//!
+//! `getrandom` is available since Glibc 2.31, but it can fail with `ENOSYS`
+//! on configurations which do not provide it. In this case we permanently
+//! switch to reading from `/dev/urandom`.
+use super::use_file;
+use crate::Error;
+use core::{
+    mem::MaybeUninit,
+    ptr::NonNull,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use use_file::util_libc;
+
+pub use crate::util::{fill_nonblocking_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
+
+static GETRANDOM_UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+
+// prevent inlining of the fallback implementation
+#[inline(never)]
+fn use_file_fallback(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    use_file::fill_inner(dest)
+}
+
+pub fn init_inner() -> Result<(), Error> {
+    if !GETRANDOM_UNAVAILABLE.load(Ordering::Relaxed) {
+        // Check that `getrandom` is supported with an empty request
+        let ret = unsafe { libc::getrandom(NonNull::dangling().as_ptr(), 0, 0) };
+        if ret >= 0 || util_libc::last_os_error().raw_os_error() != Some(libc::ENOSYS) {
+            return Ok(());
+        }
+        GETRANDOM_UNAVAILABLE.store(true, Ordering::Relaxed);
+    }
+    use_file::init_inner()
+}
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    if !GETRANDOM_UNAVAILABLE.load(Ordering::Relaxed) {
+        let res = util_libc::sys_fill_exact(dest, |buf| unsafe {
+            libc::getrandom(buf.as_mut_ptr().cast(), buf.len(), 0)
+        });
+        match res {
+            Err(err) if err.raw_os_error() == Some(libc::ENOSYS) => {
+                GETRANDOM_UNAVAILABLE.store(true, Ordering::Relaxed);
+            }
+            res => return res,
+        }
+    }
+    use_file_fallback(dest)
+}
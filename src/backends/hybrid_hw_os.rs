@@ -0,0 +1,242 @@
+//! Backend which serves requests from the CPU's RNG and periodically checks
+//! it against the OS
+//!
+//! The hardware source is RDRAND on x86(-64) and RNDR on AArch64. The OS
+//! source is selected with the `getrandom_hybrid_os` configuration flag.
+//! Supported values are `"linux_getrandom"`, `"use_file"` and `"custom"`.
+//!
+//! On every N-th call (starting with the first one), a sample is read from
+//! both sources before serving the request. If the two samples are identical,
+//! the hardware source is assumed to be stuck or tampered with and the call
+//! fails with [`Error::HW_MATCHES_OS`]. N defaults to 64 and can be changed
+//! with the `GETRANDOM_HYBRID_CHECK_INTERVAL` environment variable at build
+//! time.
+//!
+//! If the CPU does not support the hardware source, all requests are served
+//! by the OS source instead.
+//!
+//! Note that all of these sources are uncapped (see `MAX_FILL_CHUNK`).
+use crate::{
+    util::{parse_decimal, slice_as_uninit_mut, volatile_zero},
+    Error,
+};
+use core::{
+    mem::MaybeUninit,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering::Relaxed},
+};
+
+pub use crate::util::{fill_nonblocking_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
+
+cfg_if! {
+    if #[cfg(any(target_arch = "x86_64", target_arch = "x86"))] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` and `init_inner` are used
+        #[path = "rdrand.rs"]
+        mod hw;
+        const HW_UNSUPPORTED: Error = Error::NO_RDRAND;
+    } else if #[cfg(target_arch = "aarch64")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` and `init_inner` are used
+        #[path = "rndr.rs"]
+        mod hw;
+        const HW_UNSUPPORTED: Error = Error::RNDR_NOT_AVAILABLE;
+    } else {
+        compile_error!("`hybrid_hw_os` backend can be enabled only for x86, x86-64 and AArch64 targets!");
+    }
+}
+
+cfg_if! {
+    if #[cfg(getrandom_hybrid_os = "linux_getrandom")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` and `init_inner` are used
+        #[path = "linux_android.rs"]
+        mod os;
+    } else if #[cfg(getrandom_hybrid_os = "use_file")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` and `init_inner` are used
+        #[path = "use_file.rs"]
+        mod os;
+    } else if #[cfg(getrandom_hybrid_os = "custom")] {
+        #[allow(dead_code, unused_imports)] // only `fill_inner` and `init_inner` are used
+        #[path = "custom.rs"]
+        mod os;
+    } else {
+        compile_error!("`hybrid_hw_os` backend requires a valid `getrandom_hybrid_os` value!");
+    }
+}
+
+/// Number of calls between two cross-source checks
+const CHECK_INTERVAL: usize = match option_env!("GETRANDOM_HYBRID_CHECK_INTERVAL") {
+    Some(n) => {
+        const ERR: &str = "GETRANDOM_HYBRID_CHECK_INTERVAL must be a positive decimal usize";
+        let n = parse_decimal(n, ERR);
+        assert!(n > 0 && n <= usize::MAX as u64, "{}", ERR);
+        #[allow(clippy::cast_possible_truncation)] // checked above
+        let n = n as usize;
+        n
+    }
+    None => 64,
+};
+
+/// Length of the samples compared by the cross-source check
+const SAMPLE_LEN: usize = 16;
+
+struct State {
+    /// Number of calls so far, used to schedule the checks
+    calls: AtomicUsize,
+    /// Set once the hardware source was found to be unsupported
+    use_os: AtomicBool,
+}
+
+impl State {
+    const fn new() -> Self {
+        Self {
+            calls: AtomicUsize::new(0),
+            use_os: AtomicBool::new(false),
+        }
+    }
+}
+
+static STATE: State = State::new();
+
+pub fn init_inner() -> Result<(), Error> {
+    match hw::init_inner() {
+        Err(HW_UNSUPPORTED) => STATE.use_os.store(true, Relaxed),
+        res => res?,
+    }
+    os::init_inner()
+}
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    hybrid(dest, CHECK_INTERVAL, &STATE, hw::fill_inner, os::fill_inner)
+}
+
+fn hybrid(
+    dest: &mut [MaybeUninit<u8>],
+    interval: usize,
+    state: &State,
+    hw: impl Fn(&mut [MaybeUninit<u8>]) -> Result<(), Error>,
+    os: impl Fn(&mut [MaybeUninit<u8>]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    if !state.use_os.load(Relaxed) {
+        let res = if state.calls.fetch_add(1, Relaxed) % interval == 0 {
+            cross_check(&hw, &os).and_then(|()| hw(dest))
+        } else {
+            hw(dest)
+        };
+        match res {
+            Err(HW_UNSUPPORTED) => state.use_os.store(true, Relaxed),
+            res => return res,
+        }
+    }
+    os(dest)
+}
+
+/// Read a sample from both sources and fail if they are identical.
+fn cross_check(
+    hw: impl Fn(&mut [MaybeUninit<u8>]) -> Result<(), Error>,
+    os: impl Fn(&mut [MaybeUninit<u8>]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let mut hw_sample = [0u8; SAMPLE_LEN];
+    let mut os_sample = [0u8; SAMPLE_LEN];
+    // SAFETY: the sources only write initialized bytes
+    let mut res = hw(unsafe { slice_as_uninit_mut(&mut hw_sample) })
+        .and_then(|()| os(unsafe { slice_as_uninit_mut(&mut os_sample) }));
+    if res.is_ok() && hw_sample == os_sample {
+        res = Err(Error::HW_MATCHES_OS);
+    }
+    // Do not leave the samples on the stack
+    volatile_zero(&mut hw_sample);
+    volatile_zero(&mut os_sample);
+    res
+}
+
+impl Error {
+    /// The hardware source returned the same sample as the OS source.
+    // Codes 10 and 11 are used by the hardware sources.
+    pub(crate) const HW_MATCHES_OS: Error = Self::new_internal(12);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hybrid, Error, MaybeUninit, State, HW_UNSUPPORTED};
+    use core::{
+        cell::Cell,
+        sync::atomic::{AtomicU8, Ordering::Relaxed},
+    };
+
+    /// Source which fills buffers with an incrementing byte counter
+    fn counter(buf: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+        static COUNTER: AtomicU8 = AtomicU8::new(0);
+        for b in buf {
+            b.write(COUNTER.fetch_add(1, Relaxed));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_hybrid_cadence() {
+        let state = State::new();
+        let os_calls = Cell::new(0);
+        let os = |buf: &mut [MaybeUninit<u8>]| {
+            os_calls.set(os_calls.get() + 1);
+            counter(buf)
+        };
+        let mut buf = [MaybeUninit::uninit(); 8];
+        let mut os_calls_after = [0; 9];
+        for n in os_calls_after.iter_mut() {
+            hybrid(&mut buf, 4, &state, counter, os).unwrap();
+            *n = os_calls.get();
+        }
+        // Checks run on calls 0, 4 and 8
+        assert_eq!(os_calls_after, [1, 1, 1, 1, 2, 2, 2, 2, 3]);
+    }
+
+    #[test]
+    fn test_hybrid_stuck() {
+        let state = State::new();
+        let stuck = |buf: &mut [MaybeUninit<u8>]| {
+            for b in buf {
+                b.write(0xAA);
+            }
+            Ok(())
+        };
+        let mut buf = [MaybeUninit::uninit(); 8];
+        assert_eq!(
+            hybrid(&mut buf, 2, &state, stuck, stuck),
+            Err(Error::HW_MATCHES_OS)
+        );
+        // Calls between checks are not affected
+        assert_eq!(hybrid(&mut buf, 2, &state, stuck, stuck), Ok(()));
+        assert_eq!(
+            hybrid(&mut buf, 2, &state, stuck, stuck),
+            Err(Error::HW_MATCHES_OS)
+        );
+    }
+
+    #[test]
+    fn test_hybrid_unsupported() {
+        let state = State::new();
+        let hw_calls = Cell::new(0);
+        let hw = |_: &mut [MaybeUninit<u8>]| {
+            hw_calls.set(hw_calls.get() + 1);
+            Err(HW_UNSUPPORTED)
+        };
+        let mut buf = [MaybeUninit::uninit(); 8];
+        for _ in 0..3 {
+            assert_eq!(hybrid(&mut buf, 1, &state, hw, counter), Ok(()));
+        }
+        assert_eq!(hw_calls.get(), 1);
+    }
+
+    #[test]
+    fn test_hybrid_errors() {
+        let state = State::new();
+        let fail = |_: &mut [MaybeUninit<u8>]| Err(Error::UNEXPECTED);
+        let mut buf = [MaybeUninit::uninit(); 8];
+        assert_eq!(
+            hybrid(&mut buf, 1, &state, fail, counter),
+            Err(Error::UNEXPECTED)
+        );
+        assert_eq!(
+            hybrid(&mut buf, 1, &state, counter, fail),
+            Err(Error::UNEXPECTED)
+        );
+    }
+}
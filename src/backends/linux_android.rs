@@ -2,7 +2,7 @@
 use crate::Error;
 use core::mem::MaybeUninit;
 
-pub use crate::util::{inner_u32, inner_u64};
+pub use crate::util::{init_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
 
 #[path = "../util_libc.rs"]
 mod util_libc;
@@ -10,8 +10,27 @@ mod util_libc;
 #[cfg(not(any(target_os = "android", target_os = "linux")))]
 compile_error!("`linux_getrandom` backend can be enabled only for Linux/Android targets!");
 
+#[cfg(getrandom_linux_rdrand_mix)]
+#[path = "linux_rdrand_mix.rs"]
+mod rdrand_mix;
+
 pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    let getrandom_fn = |buf: &mut [MaybeUninit<u8>], flags| unsafe {
+        libc::getrandom(buf.as_mut_ptr().cast(), buf.len(), flags)
+    };
+    #[cfg(getrandom_linux_rdrand_mix)]
+    {
+        rdrand_mix::fill_inner(dest, getrandom_fn)
+    }
+    #[cfg(not(getrandom_linux_rdrand_mix))]
+    {
+        util_libc::sys_fill_exact(dest, |buf| getrandom_fn(buf, 0))
+    }
+}
+
+pub fn fill_nonblocking_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
     util_libc::sys_fill_exact(dest, |buf| unsafe {
-        libc::getrandom(buf.as_mut_ptr().cast(), buf.len(), 0)
+        libc::getrandom(buf.as_mut_ptr().cast(), buf.len(), libc::GRND_NONBLOCK)
     })
+    .map_err(util_libc::map_eagain)
 }
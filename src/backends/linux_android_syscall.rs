@@ -0,0 +1,49 @@
+//! Implementation for Linux / Android using `libc::syscall(SYS_getrandom)`
+//!
+//! Unlike `linux_android`, this does not use the `getrandom` wrapper provided
+//! by the C library, so it also works with C libraries which predate it (glibc
+//! before 2.25, musl before 1.1.20, Android before API level 28). There is no
+//! `/dev/urandom` fallback, so Linux 3.17 or later is required.
+use crate::Error;
+use core::mem::MaybeUninit;
+
+pub use crate::util::{init_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
+
+#[path = "../util_libc.rs"]
+mod util_libc;
+
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+compile_error!("`getrandom_libc` backend can be enabled only for Linux/Android targets!");
+
+#[cfg(getrandom_linux_rdrand_mix)]
+#[path = "linux_rdrand_mix.rs"]
+mod rdrand_mix;
+
+fn getrandom_fn(buf: &mut [MaybeUninit<u8>], flags: libc::c_uint) -> libc::ssize_t {
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_getrandom,
+            buf.as_mut_ptr().cast::<core::ffi::c_void>(),
+            buf.len(),
+            flags,
+        )
+    };
+    // The return value is at most `buf.len()` or -1, so it fits into `ssize_t`
+    libc::ssize_t::try_from(ret).unwrap_or(-1)
+}
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    #[cfg(getrandom_linux_rdrand_mix)]
+    {
+        rdrand_mix::fill_inner(dest, getrandom_fn)
+    }
+    #[cfg(not(getrandom_linux_rdrand_mix))]
+    {
+        util_libc::sys_fill_exact(dest, |buf| getrandom_fn(buf, 0))
+    }
+}
+
+pub fn fill_nonblocking_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    util_libc::sys_fill_exact(dest, |buf| getrandom_fn(buf, libc::GRND_NONBLOCK))
+        .map_err(util_libc::map_eagain)
+}
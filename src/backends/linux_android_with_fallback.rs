@@ -9,7 +9,7 @@ use core::{
 };
 use use_file::util_libc;
 
-pub use crate::util::{inner_u32, inner_u64};
+pub use crate::util::{inner_u32, inner_u64, MAX_FILL_CHUNK};
 
 type GetRandomFn = unsafe extern "C" fn(*mut c_void, libc::size_t, libc::c_uint) -> libc::ssize_t;
 
@@ -19,6 +19,10 @@ const NOT_AVAILABLE: NonNull<c_void> = unsafe { NonNull::new_unchecked(usize::MA
 
 static GETRANDOM_FN: AtomicPtr<c_void> = AtomicPtr::new(ptr::null_mut());
 
+#[cfg(getrandom_linux_rdrand_mix)]
+#[path = "linux_rdrand_mix.rs"]
+mod rdrand_mix;
+
 #[cold]
 fn init() -> NonNull<c_void> {
     static NAME: &[u8] = b"getrandom\0";
@@ -59,6 +63,19 @@ fn use_file_fallback(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
     use_file::fill_inner(dest)
 }
 
+pub fn init_inner() -> Result<(), Error> {
+    let raw_ptr = GETRANDOM_FN.load(Ordering::Acquire);
+    let fptr = match NonNull::new(raw_ptr) {
+        Some(p) => p,
+        None => init(),
+    };
+    if fptr == NOT_AVAILABLE {
+        use_file::init_inner()
+    } else {
+        Ok(())
+    }
+}
+
 pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
     // Despite being only a single atomic variable, we still cannot always use
     // Ordering::Relaxed, as we need to make sure a successful call to `init`
@@ -76,9 +93,36 @@ pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
         use_file_fallback(dest)
     } else {
         // note: `transume` is currently the only way to convert pointer into function reference
+        let getrandom_fn = unsafe { mem::transmute::<NonNull<c_void>, GetRandomFn>(fptr) };
+        let getrandom_fn = |buf: &mut [MaybeUninit<u8>], flags| unsafe {
+            getrandom_fn(buf.as_mut_ptr().cast(), buf.len(), flags)
+        };
+        #[cfg(getrandom_linux_rdrand_mix)]
+        {
+            rdrand_mix::fill_inner(dest, getrandom_fn)
+        }
+        #[cfg(not(getrandom_linux_rdrand_mix))]
+        {
+            util_libc::sys_fill_exact(dest, |buf| getrandom_fn(buf, 0))
+        }
+    }
+}
+
+pub fn fill_nonblocking_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    let raw_ptr = GETRANDOM_FN.load(Ordering::Acquire);
+    let fptr = match NonNull::new(raw_ptr) {
+        Some(p) => p,
+        None => init(),
+    };
+
+    if fptr == NOT_AVAILABLE {
+        // Waiting for `/dev/random` to become readable can not be avoided
+        use_file_fallback(dest)
+    } else {
         let getrandom_fn = unsafe { mem::transmute::<NonNull<c_void>, GetRandomFn>(fptr) };
         util_libc::sys_fill_exact(dest, |buf| unsafe {
-            getrandom_fn(buf.as_mut_ptr().cast(), buf.len(), 0)
+            getrandom_fn(buf.as_mut_ptr().cast(), buf.len(), libc::GRND_NONBLOCK)
         })
+        .map_err(util_libc::map_eagain)
     }
 }
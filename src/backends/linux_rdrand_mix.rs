@@ -0,0 +1,39 @@
+//! Opt-in RDRAND fallback for Linux when `getrandom(2)` would block
+//!
+//! With `--cfg getrandom_linux_rdrand_mix` the `getrandom` system call is
+//! invoked with `GRND_NONBLOCK`. If it fails with `EAGAIN` (i.e. the kernel
+//! entropy pool is not initialized yet) and the CPU supports RDRAND, the
+//! buffer is filled with RDRAND output instead. Otherwise we fall back to
+//! the blocking call.
+use super::util_libc;
+use crate::Error;
+use core::mem::MaybeUninit;
+
+#[cfg(not(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "x86"))))]
+compile_error!(
+    "`getrandom_linux_rdrand_mix` can be enabled only for x86 and x86-64 Linux targets!"
+);
+
+// Only `fill_inner` is used
+#[allow(dead_code, unused_imports)]
+#[path = "rdrand.rs"]
+mod rdrand;
+
+/// Fill `dest` using `getrandom_fn`, which takes a buffer and `getrandom(2)` flags.
+pub(super) fn fill_inner(
+    dest: &mut [MaybeUninit<u8>],
+    getrandom_fn: impl Fn(&mut [MaybeUninit<u8>], libc::c_uint) -> libc::ssize_t,
+) -> Result<(), Error> {
+    // `getrandom_test_linux_rdrand_mix` simulates an uninitialized entropy pool
+    if !cfg!(getrandom_test_linux_rdrand_mix) {
+        let res = util_libc::sys_fill_exact(dest, |buf| getrandom_fn(buf, libc::GRND_NONBLOCK));
+        match res {
+            Err(err) if err.raw_os_error() == Some(libc::EAGAIN) => {}
+            res => return res,
+        }
+    }
+    match rdrand::fill_inner(dest) {
+        Ok(()) => Ok(()),
+        Err(_) => util_libc::sys_fill_exact(dest, |buf| getrandom_fn(buf, 0)),
+    }
+}
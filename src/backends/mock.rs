@@ -0,0 +1,98 @@
+//! Mock backend which plays back bytes set by tests
+//!
+//! WARNING: this backend is NOT random! It returns exactly the bytes set
+//! with [`set_mock_bytes`], in order, and fails with
+//! [`Error::MOCK_EXHAUSTED`] once they run out.
+//! It MUST NOT be used outside of tests.
+extern crate std;
+
+use crate::{util::slice_as_uninit, Error};
+use core::mem::MaybeUninit;
+use std::{
+    sync::{Mutex, MutexGuard},
+    vec::Vec,
+};
+
+pub use crate::util::{fill_nonblocking_inner, init_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
+
+#[cfg(not(feature = "std"))]
+compile_error!("the `mock` backend requires the `std` feature");
+
+/// Bytes to play back and the position of the next byte to return.
+static STATE: Mutex<(Vec<u8>, usize)> = Mutex::new((Vec::new(), 0));
+
+fn state() -> MutexGuard<'static, (Vec<u8>, usize)> {
+    // A panicking test can't leave the state inconsistent, so ignore poisoning.
+    STATE.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Replace the bytes returned by the following calls with `bytes`.
+///
+/// Each call consumes as many bytes as it requests. A call which requests
+/// more bytes than remain fails with [`Error::MOCK_EXHAUSTED`] without
+/// consuming any of them.
+///
+/// Only available with the `mock` backend.
+pub fn set_mock_bytes(bytes: &[u8]) {
+    let mut state = state();
+    state.0.clear();
+    state.0.extend_from_slice(bytes);
+    state.1 = 0;
+}
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    let mut state = state();
+    let (bytes, pos) = &mut *state;
+    let src = pos
+        .checked_add(dest.len())
+        .and_then(|end| bytes.get(*pos..end))
+        .ok_or(Error::MOCK_EXHAUSTED)?;
+    dest.copy_from_slice(slice_as_uninit(src));
+    *pos += dest.len();
+    Ok(())
+}
+
+impl Error {
+    /// All bytes set with `set_mock_bytes` have been consumed.
+    ///
+    /// Only available with the `mock` backend.
+    pub const MOCK_EXHAUSTED: Error = Self::new_internal(10);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::set_mock_bytes;
+    use crate::Error;
+
+    #[test]
+    fn test_mock() {
+        set_mock_bytes(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+        let mut buf = [0u8; 3];
+        crate::fill(&mut buf).unwrap();
+        assert_eq!(buf, [1, 2, 3]);
+        assert_eq!(crate::u32(), Ok(u32::from_ne_bytes([4, 5, 6, 7])));
+        assert_eq!(
+            crate::u64(),
+            Ok(u64::from_ne_bytes([8, 9, 10, 11, 12, 13, 14, 15]))
+        );
+        // Empty requests don't call the backend
+        crate::fill(&mut []).unwrap();
+        assert_eq!(crate::fill(&mut buf[..1]), Err(Error::MOCK_EXHAUSTED));
+
+        set_mock_bytes(&[42, 43]);
+        assert_eq!(crate::fill(&mut buf), Err(Error::MOCK_EXHAUSTED));
+        crate::fill(&mut buf[..2]).unwrap();
+        assert_eq!(buf[..2], [42, 43]);
+        assert_eq!(crate::u32(), Err(Error::MOCK_EXHAUSTED));
+
+        // Explicit-endian integers depend only on the byte stream
+        set_mock_bytes(&[1, 2, 3, 4, 1, 2, 3, 4]);
+        assert_eq!(crate::u32_le(), Ok(0x0403_0201));
+        assert_eq!(crate::u32_be(), Ok(0x0102_0304));
+
+        set_mock_bytes(&[1, 2, 3, 4, 5, 6, 7, 8, 1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(crate::u64_le(), Ok(0x0807_0605_0403_0201));
+        assert_eq!(crate::u64_be(), Ok(0x0102_0304_0506_0708));
+        assert_eq!(crate::u32_le(), Err(Error::MOCK_EXHAUSTED));
+    }
+}
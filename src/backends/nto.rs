@@ -0,0 +1,75 @@
+//! Implementation for QNX Neutrino
+//!
+//! `getrandom` was introduced in QNX SDP 8.0. To support older versions we
+//! implement our own weak linkage to it, and provide a fallback based on
+//! reading from `/dev/urandom`.
+use super::use_file;
+use crate::Error;
+use core::{
+    ffi::c_void,
+    mem::{self, MaybeUninit},
+    ptr::{self, NonNull},
+    sync::atomic::{AtomicPtr, Ordering},
+};
+use use_file::util_libc;
+
+pub use crate::util::{fill_nonblocking_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
+
+type GetRandomFn = unsafe extern "C" fn(*mut c_void, libc::size_t, libc::c_uint) -> libc::ssize_t;
+
+/// Sentinel value which indicates that `getrandom` is not available.
+const NOT_AVAILABLE: NonNull<c_void> = unsafe { NonNull::new_unchecked(usize::MAX as *mut c_void) };
+
+static GETRANDOM_FN: AtomicPtr<c_void> = AtomicPtr::new(ptr::null_mut());
+
+#[cold]
+fn init() -> NonNull<c_void> {
+    static NAME: &[u8] = b"getrandom\0";
+    let name_ptr = NAME.as_ptr().cast::<libc::c_char>();
+    let raw_ptr = unsafe { libc::dlsym(libc::RTLD_DEFAULT, name_ptr) };
+    let res_ptr = match NonNull::new(raw_ptr) {
+        Some(_) if cfg!(getrandom_test_nto_fallback) => NOT_AVAILABLE,
+        Some(fptr) => fptr,
+        None => NOT_AVAILABLE,
+    };
+    GETRANDOM_FN.store(res_ptr.as_ptr(), Ordering::Release);
+    res_ptr
+}
+
+// prevent inlining of the fallback implementation
+#[inline(never)]
+fn use_file_fallback(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    use_file::fill_inner(dest)
+}
+
+pub fn init_inner() -> Result<(), Error> {
+    let raw_ptr = GETRANDOM_FN.load(Ordering::Acquire);
+    let fptr = match NonNull::new(raw_ptr) {
+        Some(p) => p,
+        None => init(),
+    };
+    if fptr == NOT_AVAILABLE {
+        use_file::init_inner()
+    } else {
+        Ok(())
+    }
+}
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    // See the comment in `linux_android_with_fallback` on the used orderings.
+    let raw_ptr = GETRANDOM_FN.load(Ordering::Acquire);
+    let fptr = match NonNull::new(raw_ptr) {
+        Some(p) => p,
+        None => init(),
+    };
+
+    if fptr == NOT_AVAILABLE {
+        use_file_fallback(dest)
+    } else {
+        // note: `transmute` is currently the only way to convert pointer into function reference
+        let getrandom_fn = unsafe { mem::transmute::<NonNull<c_void>, GetRandomFn>(fptr) };
+        util_libc::sys_fill_exact(dest, |buf| unsafe {
+            getrandom_fn(buf.as_mut_ptr().cast(), buf.len(), 0)
+        })
+    }
+}
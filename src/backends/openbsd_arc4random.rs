@@ -0,0 +1,28 @@
+//! Implementation for OpenBSD using arc4random(3)
+//!
+//! arc4random(3) is a ChaCha20-based CSPRNG seeded and regularly reseeded by
+//! the kernel. Unlike getentropy(2), which is intended for seeding, it is the
+//! recommended interface for application randomness on OpenBSD. It never fails
+//! and does not perform a system call for every request.
+use crate::Error;
+use core::mem::MaybeUninit;
+
+pub use crate::util::{fill_nonblocking_inner, init_inner, MAX_FILL_CHUNK};
+
+#[cfg(not(target_os = "openbsd"))]
+compile_error!("`openbsd_arc4random` backend can be enabled only for OpenBSD targets!");
+
+pub fn inner_u32() -> Result<u32, Error> {
+    Ok(unsafe { libc::arc4random() })
+}
+
+pub fn inner_u64() -> Result<u64, Error> {
+    let a = unsafe { libc::arc4random() };
+    let b = unsafe { libc::arc4random() };
+    Ok((u64::from(a) << 32) | u64::from(b))
+}
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    unsafe { libc::arc4random_buf(dest.as_mut_ptr().cast(), dest.len()) };
+    Ok(())
+}
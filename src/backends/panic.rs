@@ -0,0 +1,24 @@
+//! Backend which panics when random data is requested
+//!
+//! A development stopgap for getting code to compile on targets without a
+//! supported entropy source: requesting random data panics with a clear
+//! message instead of failing at compile or link time (use `RUST_BACKTRACE=1`
+//! to find the caller). It MUST be replaced with a real backend before the
+//! code is used.
+use crate::Error;
+use core::mem::MaybeUninit;
+
+pub use crate::util::{fill_nonblocking_inner, init_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
+
+pub fn fill_inner(_dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    panic!("getrandom: no entropy source configured for this target")
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[should_panic(expected = "getrandom: no entropy source configured for this target")]
+    fn test_panic() {
+        let _ = crate::fill(&mut [0u8; 1]);
+    }
+}
@@ -0,0 +1,121 @@
+//! Backend which forwards requests to a function set at runtime
+//!
+//! Until a function is set with [`set_proxy`], all calls fail with
+//! [`Error::UNSUPPORTED`].
+use crate::Error;
+use core::{
+    mem::{self, MaybeUninit},
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+pub use crate::util::{fill_nonblocking_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
+
+/// Function which handles all requests with the `proxy` backend.
+///
+/// It has the same contract as the custom backend: it MUST fully initialize
+/// the buffer when it returns `Ok(())` and MUST NOT ever write uninitialized
+/// bytes into it.
+pub type ProxyFn = fn(&mut [MaybeUninit<u8>]) -> Result<(), Error>;
+
+/// Currently set proxy function, or null if no function is set.
+static PROXY: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// Set the function which handles all following requests for random data.
+///
+/// This allows selecting the source of randomness at runtime, e.g. based on
+/// configuration loaded at startup. Setting a new function replaces the
+/// previous one. Requests which are already running in other threads may
+/// still complete using the previous function.
+///
+/// The store uses `Release` ordering and the load on every request uses
+/// `Acquire` ordering, so everything written before calling `set_proxy`
+/// (e.g. the state used by `proxy`) is visible to `proxy` when it is called.
+///
+/// `proxy` must not request random data from this crate itself (directly or
+/// indirectly), since it would be called recursively.
+///
+/// Only available with the `proxy` backend.
+pub fn set_proxy(proxy: ProxyFn) {
+    PROXY.store(proxy as *mut (), Ordering::Release);
+}
+
+/// Remove the function set with [`set_proxy`], if any.
+///
+/// All following requests fail with [`Error::UNSUPPORTED`].
+///
+/// Only available with the `proxy` backend.
+pub fn clear_proxy() {
+    PROXY.store(ptr::null_mut(), Ordering::Release);
+}
+
+/// Fails with [`Error::UNSUPPORTED`] if no proxy function is set.
+pub fn init_inner() -> Result<(), Error> {
+    if PROXY.load(Ordering::Acquire).is_null() {
+        return Err(Error::UNSUPPORTED);
+    }
+    Ok(())
+}
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    let ptr = PROXY.load(Ordering::Acquire);
+    if ptr.is_null() {
+        return Err(Error::UNSUPPORTED);
+    }
+    // SAFETY: non-null values of `PROXY` are only stored by `set_proxy`
+    // and were converted from a valid `ProxyFn` function pointer.
+    let proxy = unsafe { mem::transmute::<*mut (), ProxyFn>(ptr) };
+    proxy(dest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clear_proxy, set_proxy};
+    use crate::Error;
+    use core::mem::MaybeUninit;
+
+    fn fill_ones(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+        for b in dest {
+            b.write(1);
+        }
+        Ok(())
+    }
+
+    fn fill_twos(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+        for b in dest {
+            b.write(2);
+        }
+        Ok(())
+    }
+
+    fn fail(_: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+        Err(Error::new_custom(42))
+    }
+
+    #[test]
+    fn test_proxy() {
+        let mut buf = [0u8; 4];
+        clear_proxy();
+        assert_eq!(crate::fill(&mut buf), Err(Error::UNSUPPORTED));
+        assert_eq!(crate::init(), Err(Error::UNSUPPORTED));
+        assert_eq!(crate::probe(), Err(Error::UNSUPPORTED));
+
+        set_proxy(fill_ones);
+        crate::init().unwrap();
+        crate::fill(&mut buf).unwrap();
+        assert_eq!(buf, [1; 4]);
+        assert_eq!(crate::u32(), Ok(0x0101_0101));
+
+        set_proxy(fill_twos);
+        crate::fill(&mut buf).unwrap();
+        assert_eq!(buf, [2; 4]);
+        assert_eq!(crate::u64(), Ok(0x0202_0202_0202_0202));
+
+        set_proxy(fail);
+        assert_eq!(crate::fill(&mut buf), Err(Error::new_custom(42)));
+        assert_eq!(crate::u32(), Err(Error::new_custom(42)));
+
+        clear_proxy();
+        assert_eq!(crate::u64(), Err(Error::UNSUPPORTED));
+    }
+}
@@ -1,7 +1,12 @@
 //! RDRAND backend for x86(-64) targets
-use crate::{util::slice_as_uninit, Error};
+use crate::{
+    util::{parse_decimal, slice_as_uninit},
+    Error,
+};
 use core::mem::{size_of, MaybeUninit};
 
+pub use crate::util::{fill_nonblocking_inner, MAX_FILL_CHUNK};
+
 #[path = "../lazy.rs"]
 mod lazy;
 
@@ -20,24 +25,48 @@ cfg_if! {
     }
 }
 
+/// Result of the CPUID check and self-test, shared by all entry points.
 static RDRAND_GOOD: lazy::LazyBool = lazy::LazyBool::new();
 
 // Recommendation from "Intel® Digital Random Number Generator (DRNG) Software
 // Implementation Guide" - Section 5.2.1 and "Intel® 64 and IA-32 Architectures
 // Software Developer’s Manual" - Volume 1 - Section 7.3.17.1.
-const RETRY_LIMIT: usize = 10;
+//
+// The limit can be raised with the `GETRANDOM_RDRAND_RETRIES` environment
+// variable at build time, e.g. for virtualized CPUs with high RDRAND contention.
+const RETRY_LIMIT: u64 = match option_env!("GETRANDOM_RDRAND_RETRIES") {
+    Some(n) => {
+        const ERR: &str = "GETRANDOM_RDRAND_RETRIES must be a positive decimal u64";
+        let n = parse_decimal(n, ERR);
+        assert!(n > 0, "{}", ERR);
+        n
+    }
+    None => 10,
+};
 
-#[target_feature(enable = "rdrand")]
-unsafe fn rdrand() -> Option<Word> {
+/// Call `step` until it succeeds, at most `RETRY_LIMIT` times.
+#[inline(always)]
+fn retry<T>(mut step: impl FnMut() -> Option<T>) -> Option<T> {
     for _ in 0..RETRY_LIMIT {
-        let mut val = 0;
-        if rdrand_step(&mut val) == 1 {
+        if let Some(val) = step() {
             return Some(val);
         }
     }
     None
 }
 
+#[target_feature(enable = "rdrand")]
+unsafe fn rdrand() -> Option<Word> {
+    retry(|| {
+        let mut val = 0;
+        if rdrand_step(&mut val) == 1 {
+            Some(val)
+        } else {
+            None
+        }
+    })
+}
+
 // "rdrand" target feature requires "+rdrand" flag, see https://github.com/rust-lang/rust/issues/49653.
 #[cfg(all(target_env = "sgx", not(target_feature = "rdrand")))]
 compile_error!(
@@ -147,33 +176,78 @@ unsafe fn rdrand_u64() -> Option<u64> {
     Some((u64::from(a) << 32) | u64::from(b))
 }
 
-pub fn inner_u32() -> Result<u32, Error> {
-    if !RDRAND_GOOD.unsync_init(is_rdrand_good) {
-        return Err(Error::NO_RDRAND);
+/// Check (once) that RDRAND is supported and working.
+///
+/// All entry points go through this function, so they always agree
+/// on whether RDRAND can be used.
+#[inline]
+fn check_rdrand() -> Result<(), Error> {
+    if RDRAND_GOOD.unsync_init(is_rdrand_good) {
+        Ok(())
+    } else {
+        Err(Error::NO_RDRAND)
     }
+}
+
+pub fn init_inner() -> Result<(), Error> {
+    check_rdrand()
+}
+
+pub fn inner_u32() -> Result<u32, Error> {
+    check_rdrand()?;
     // SAFETY: After this point, we know rdrand is supported.
     unsafe { rdrand_u32() }.ok_or(Error::FAILED_RDRAND)
 }
 
 pub fn inner_u64() -> Result<u64, Error> {
-    if !RDRAND_GOOD.unsync_init(is_rdrand_good) {
-        return Err(Error::NO_RDRAND);
-    }
+    check_rdrand()?;
     // SAFETY: After this point, we know rdrand is supported.
     unsafe { rdrand_u64() }.ok_or(Error::FAILED_RDRAND)
 }
 
 pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
-    if !RDRAND_GOOD.unsync_init(is_rdrand_good) {
-        return Err(Error::NO_RDRAND);
-    }
+    check_rdrand()?;
     // SAFETY: After this point, we know rdrand is supported.
     unsafe { rdrand_exact(dest) }.ok_or(Error::FAILED_RDRAND)
 }
 
-impl Error {
-    /// RDRAND instruction failed due to a hardware issue.
-    pub(crate) const FAILED_RDRAND: Error = Self::new_internal(10);
-    /// RDRAND instruction unsupported on this target.
-    pub(crate) const NO_RDRAND: Error = Self::new_internal(11);
+#[cfg(test)]
+mod tests {
+    use super::{
+        fill_inner, init_inner, inner_u32, inner_u64, is_rdrand_good, retry, Error, MaybeUninit,
+        RETRY_LIMIT,
+    };
+
+    fn fail_times(n: u64) -> Option<u64> {
+        let mut calls = 0;
+        retry(|| {
+            calls += 1;
+            if calls > n {
+                Some(calls)
+            } else {
+                None
+            }
+        })
+    }
+
+    #[test]
+    fn test_retry() {
+        assert_eq!(fail_times(0), Some(1));
+        assert_eq!(fail_times(RETRY_LIMIT - 1), Some(RETRY_LIMIT));
+        assert_eq!(fail_times(RETRY_LIMIT), None);
+    }
+
+    #[test]
+    fn test_entry_points_agree() {
+        let supported = is_rdrand_good();
+        let expected = |res: Result<(), Error>| match res {
+            Ok(()) => assert!(supported),
+            Err(err) => assert_eq!((supported, err), (false, Error::NO_RDRAND)),
+        };
+        expected(init_inner());
+        expected(inner_u32().map(drop));
+        expected(inner_u64().map(drop));
+        let mut buf = [MaybeUninit::uninit(); 16];
+        expected(fill_inner(&mut buf));
+    }
 }
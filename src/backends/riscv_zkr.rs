@@ -0,0 +1,192 @@
+//! Zkr entropy source backend for RISC-V targets
+//!
+//! RISC-V Cryptography Extensions Volume I: Scalar & Entropy Source
+//! Instructions, Version 1.0.1, Section 4 "Entropy Source"
+//!
+//! The `seed` CSR provides 16 bits of raw entropy per successful read. It is
+//! intended for seeding a CSPRNG and is usually only accessible from M-mode
+//! (or from S/U-mode if enabled by `mseccfg.SSEED`/`mseccfg.USEED`), so this
+//! backend is mostly useful on bare-metal targets. Accessing it when it's not
+//! enabled for the current privilege mode raises an illegal instruction
+//! exception.
+use crate::{util::slice_as_uninit, Error};
+use core::mem::{size_of, MaybeUninit};
+
+pub use crate::util::{fill_nonblocking_inner, init_inner, MAX_FILL_CHUNK};
+
+#[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
+compile_error!("the `riscv_zkr` backend can be enabled only for RISC-V targets!");
+
+#[cfg(not(target_feature = "zkr"))]
+compile_error!(
+    "the `riscv_zkr` backend requires the 'zkr' target feature. \
+    Enable by using -C target-feature=+zkr."
+);
+
+/// Maximum number of reads which may report `BIST` or `WAIT` before giving up
+/// when collecting a single 16-bit word. The spec does not bound the time spent
+/// in these states, so this limit is conservative.
+const RETRY_LIMIT: usize = 1000;
+
+/// Value of a `seed` CSR read, decoded from its `OPST` field (bits 31:30).
+#[derive(Debug, PartialEq, Eq)]
+enum Status {
+    /// Built-in self-test is running, no entropy available yet.
+    Bist,
+    /// No entropy available yet, the read should be retried.
+    Wait,
+    /// 16 bits of entropy are available.
+    Es16(u16),
+    /// Unrecoverable self-test failure.
+    Dead,
+}
+
+fn decode(seed: u32) -> Status {
+    match seed >> 30 {
+        0b00 => Status::Bist,
+        0b01 => Status::Wait,
+        0b10 => Status::Es16(u16::try_from(seed & 0xffff).expect("masked to 16 bits")),
+        _ => Status::Dead,
+    }
+}
+
+/// Read the `seed` CSR (0x015).
+///
+/// It has to be accessed with a read-write instruction, a read-only
+/// access (e.g. `csrr`) raises an illegal instruction exception.
+fn read_seed() -> u32 {
+    // Only the lower 32 bits are defined, the rest are zero on RV64
+    let seed: u32;
+    // SAFETY: the `zkr` target feature is enabled, and reading `seed` does
+    // not access memory. Writing `x0` to it has no effect.
+    unsafe {
+        core::arch::asm!(
+            "csrrw {seed}, 0x015, x0",
+            seed = out(reg) seed,
+            options(nomem, nostack),
+        );
+    }
+    seed
+}
+
+/// Collect 16 bits of entropy using `read`, retrying on `BIST` and `WAIT`.
+fn read_es16(mut read: impl FnMut() -> u32) -> Result<u16, Error> {
+    for _ in 0..RETRY_LIMIT {
+        match decode(read()) {
+            Status::Es16(val) => return Ok(val),
+            Status::Bist | Status::Wait => core::hint::spin_loop(),
+            Status::Dead => return Err(Error::ZKR_DEAD),
+        }
+    }
+    Err(Error::ZKR_TIMEOUT)
+}
+
+fn zkr_u64(mut read: impl FnMut() -> u32) -> Result<u64, Error> {
+    let mut res = 0;
+    for _ in 0..4 {
+        res = (res << 16) | u64::from(read_es16(&mut read)?);
+    }
+    Ok(res)
+}
+
+fn zkr_fill(dest: &mut [MaybeUninit<u8>], mut read: impl FnMut() -> u32) -> Result<(), Error> {
+    let mut chunks = dest.chunks_exact_mut(size_of::<u16>());
+    for chunk in chunks.by_ref() {
+        let src = read_es16(&mut read)?.to_ne_bytes();
+        chunk.copy_from_slice(slice_as_uninit(&src));
+    }
+
+    let tail = chunks.into_remainder();
+    let n = tail.len();
+    if n > 0 {
+        let src = read_es16(&mut read)?.to_ne_bytes();
+        tail.copy_from_slice(slice_as_uninit(&src[..n]));
+    }
+    Ok(())
+}
+
+pub fn inner_u32() -> Result<u32, Error> {
+    let hi = read_es16(read_seed)?;
+    let lo = read_es16(read_seed)?;
+    Ok((u32::from(hi) << 16) | u32::from(lo))
+}
+
+pub fn inner_u64() -> Result<u64, Error> {
+    zkr_u64(read_seed)
+}
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    zkr_fill(dest, read_seed)
+}
+
+impl Error {
+    /// The Zkr entropy source reported an unrecoverable self-test failure.
+    pub(crate) const ZKR_DEAD: Error = Self::new_internal(10);
+    /// The Zkr entropy source did not provide entropy after multiple retries.
+    pub(crate) const ZKR_TIMEOUT: Error = Self::new_internal(11);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, read_es16, zkr_fill, zkr_u64, Status, RETRY_LIMIT};
+    use crate::Error;
+    use core::mem::MaybeUninit;
+
+    const BIST: u32 = 0b00 << 30;
+    const WAIT: u32 = 0b01 << 30;
+    const ES16: u32 = 0b10 << 30;
+    const DEAD: u32 = 0b11 << 30;
+
+    /// Mock `seed` CSR which returns `vals` in order and then `last` forever.
+    fn mock(vals: &[u32], last: u32) -> impl FnMut() -> u32 + '_ {
+        let mut vals = vals.iter();
+        move || vals.next().copied().unwrap_or(last)
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode(BIST), Status::Bist);
+        assert_eq!(decode(WAIT | 0x1234), Status::Wait);
+        assert_eq!(decode(ES16 | 0xabcd), Status::Es16(0xabcd));
+        // Reserved bits 29:16 are ignored
+        assert_eq!(decode(ES16 | 0x00ff_0000 | 0x42), Status::Es16(0x42));
+        assert_eq!(decode(DEAD), Status::Dead);
+    }
+
+    #[test]
+    fn test_retry() {
+        let vals = [BIST, BIST, WAIT, WAIT, ES16 | 0x1234];
+        assert_eq!(read_es16(mock(&vals, DEAD)), Ok(0x1234));
+        assert_eq!(read_es16(mock(&[WAIT, DEAD], ES16)), Err(Error::ZKR_DEAD));
+
+        let mut vals = [WAIT; RETRY_LIMIT];
+        vals[RETRY_LIMIT - 1] = ES16 | 7;
+        assert_eq!(read_es16(mock(&vals, DEAD)), Ok(7));
+        assert_eq!(read_es16(mock(&[], WAIT)), Err(Error::ZKR_TIMEOUT));
+    }
+
+    #[test]
+    fn test_collect() {
+        let vals = [
+            ES16 | 0x0123,
+            WAIT,
+            ES16 | 0x4567,
+            ES16 | 0x89ab,
+            ES16 | 0xcdef,
+        ];
+        assert_eq!(zkr_u64(mock(&vals, DEAD)), Ok(0x0123_4567_89ab_cdef));
+
+        let mut buf = [MaybeUninit::new(0u8); 5];
+        let vals = [ES16 | 0x0102, ES16 | 0x0304, ES16 | 0x0506];
+        zkr_fill(&mut buf, mock(&vals, DEAD)).unwrap();
+        let buf = buf.map(|b| unsafe { b.assume_init() });
+        let [a, b] = 0x0102u16.to_ne_bytes();
+        let [c, d] = 0x0304u16.to_ne_bytes();
+        let [e, _] = 0x0506u16.to_ne_bytes();
+        assert_eq!(buf, [a, b, c, d, e]);
+
+        let mut buf = [MaybeUninit::new(0u8); 5];
+        let vals = [ES16, ES16, DEAD];
+        assert_eq!(zkr_fill(&mut buf, mock(&vals, ES16)), Err(Error::ZKR_DEAD));
+    }
+}
@@ -9,6 +9,8 @@ use crate::{
 use core::arch::asm;
 use core::mem::{size_of, MaybeUninit};
 
+pub use crate::util::{fill_nonblocking_inner, init_inner, MAX_FILL_CHUNK};
+
 #[cfg(not(target_arch = "aarch64"))]
 compile_error!("the `rndr` backend can be enabled only for AArch64 targets!");
 
@@ -132,10 +134,3 @@ pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
         Err(Error::RNDR_NOT_AVAILABLE)
     }
 }
-
-impl Error {
-    /// RNDR register read failed due to a hardware issue.
-    pub(crate) const RNDR_FAILURE: Error = Self::new_internal(10);
-    /// RNDR register is not supported on this target.
-    pub(crate) const RNDR_NOT_AVAILABLE: Error = Self::new_internal(11);
-}
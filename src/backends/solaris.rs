@@ -15,15 +15,15 @@
 use crate::Error;
 use core::{ffi::c_void, mem::MaybeUninit};
 
-pub use crate::util::{inner_u32, inner_u64};
+pub use crate::util::{fill_nonblocking_inner, init_inner, inner_u32, inner_u64};
 
 #[path = "../util_libc.rs"]
 mod util_libc;
 
-const MAX_BYTES: usize = 1024;
+pub const MAX_FILL_CHUNK: usize = 1024;
 
 pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
-    for chunk in dest.chunks_mut(MAX_BYTES) {
+    for chunk in dest.chunks_mut(MAX_FILL_CHUNK) {
         let ptr = chunk.as_mut_ptr().cast::<c_void>();
         let ret = unsafe { libc::getrandom(ptr, chunk.len(), libc::GRND_RANDOM) };
         // In case the man page has a typo, we also check for negative ret.
@@ -0,0 +1,32 @@
+//! Implementation using the unstable `std::random` module
+//!
+//! Only selected if the build script detected that the compiler provides
+//! `std::random` for the target, otherwise the default backend is used.
+extern crate std;
+
+use crate::{util::uninit_slice_fill_zero, Error};
+use core::mem::MaybeUninit;
+use std::random::{DefaultRandomSource, RandomSource};
+
+pub use crate::util::{fill_nonblocking_inner, init_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
+
+#[cfg(not(feature = "std"))]
+compile_error!("the `stdrng` backend requires the `std` feature");
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    // `fill_bytes` can not report errors, it panics if the system fails.
+    DefaultRandomSource.fill_bytes(uninit_slice_fill_zero(dest));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_stdrng() {
+        let mut buf = [0u8; 32];
+        crate::fill(&mut buf).unwrap();
+        // Probability of failure: 2^(-128)
+        assert!(buf[16..].iter().any(|&b| b != 0));
+        assert!(crate::u64().is_ok());
+    }
+}
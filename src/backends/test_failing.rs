@@ -0,0 +1,71 @@
+//! Backend with a configurable error for testing error handling
+//!
+//! WARNING: this backend is NOT random! If an error was set with
+//! [`set_test_error`], every call returns it. Otherwise buffers are filled
+//! with an incrementing (wrapping) byte counter, like the `counter` backend.
+//! It MUST NOT be used outside of tests.
+use crate::{util::fill_counter, Error};
+use core::{
+    mem::MaybeUninit,
+    sync::atomic::{AtomicU32, AtomicU8, Ordering},
+};
+
+pub use crate::util::{fill_nonblocking_inner, init_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
+
+/// Code of the error returned by all calls, or 0 if calls should succeed.
+static ERROR_CODE: AtomicU32 = AtomicU32::new(0);
+static COUNTER: AtomicU8 = AtomicU8::new(0);
+
+/// Set the error returned by all following calls, or `None` to make them succeed.
+///
+/// Only available with the `test_failing` backend.
+pub fn set_test_error(err: Option<Error>) {
+    let code = err.map_or(0, Error::into_raw);
+    ERROR_CODE.store(code, Ordering::Relaxed);
+}
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    if let Some(err) = Error::from_raw(ERROR_CODE.load(Ordering::Relaxed)) {
+        return Err(err);
+    }
+    fill_counter(&COUNTER, dest);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::set_test_error;
+    use crate::Error;
+    use core::task::Poll;
+
+    #[test]
+    fn test_failing() {
+        let mut buf = [0u8; 4];
+        set_test_error(None);
+        crate::fill(&mut buf).unwrap();
+        assert_eq!(buf[3], buf[0].wrapping_add(3));
+
+        let errors = [Error::UNSUPPORTED, Error::new_custom(7), Error::WOULD_BLOCK];
+        for err in errors {
+            set_test_error(Some(err));
+            assert_eq!(crate::fill(&mut buf), Err(err));
+            assert_eq!(crate::u32(), Err(err));
+            assert_eq!(crate::u64(), Err(err));
+        }
+
+        set_test_error(None);
+        crate::fill(&mut buf).unwrap();
+        crate::u64().unwrap();
+
+        // Only `WOULD_BLOCK` is reported as pending
+        set_test_error(Some(Error::WOULD_BLOCK));
+        assert_eq!(crate::poll_fill(&mut buf), Poll::Pending);
+        set_test_error(Some(Error::UNSUPPORTED));
+        assert_eq!(
+            crate::poll_fill(&mut buf),
+            Poll::Ready(Err(Error::UNSUPPORTED))
+        );
+        set_test_error(None);
+        assert_eq!(crate::poll_fill(&mut buf), Poll::Ready(Ok(())));
+    }
+}
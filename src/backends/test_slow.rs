@@ -0,0 +1,67 @@
+//! Slow backend for testing code which has to cope with blocking calls
+//!
+//! Every call sleeps for the delay set with [`set_delay`] (initially zero),
+//! like a real source blocking during early boot, and then behaves like the
+//! `counter` backend, so it is NOT random and only meant for tests.
+extern crate std;
+
+use crate::{util::fill_counter, Error};
+use core::{
+    mem::MaybeUninit,
+    sync::atomic::{AtomicU64, AtomicU8, Ordering},
+    time::Duration,
+};
+
+pub use crate::util::{fill_nonblocking_inner, init_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
+
+#[cfg(not(feature = "std"))]
+compile_error!("the `test_slow` backend requires the `std` feature");
+
+/// Delay of every call in nanoseconds.
+static DELAY_NANOS: AtomicU64 = AtomicU64::new(0);
+static COUNTER: AtomicU8 = AtomicU8::new(0);
+
+/// Set the delay of all following calls.
+///
+/// Delays longer than `u64::MAX` nanoseconds (about 584 years) are saturated.
+///
+/// Only available with the `test_slow` backend.
+pub fn set_delay(delay: Duration) {
+    let nanos = u64::try_from(delay.as_nanos()).unwrap_or(u64::MAX);
+    DELAY_NANOS.store(nanos, Ordering::Relaxed);
+}
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    let nanos = DELAY_NANOS.load(Ordering::Relaxed);
+    if nanos != 0 {
+        std::thread::sleep(Duration::from_nanos(nanos));
+    }
+    fill_counter(&COUNTER, dest);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::set_delay;
+    use core::time::Duration;
+    use std::time::Instant;
+
+    #[test]
+    fn test_slow() {
+        const DELAY: Duration = Duration::from_millis(50);
+        let mut buf = [0u8; 4];
+
+        set_delay(DELAY);
+        let start = Instant::now();
+        crate::fill(&mut buf).unwrap();
+        assert!(start.elapsed() >= DELAY);
+        assert_eq!(buf[3], buf[0].wrapping_add(3));
+
+        let start = Instant::now();
+        crate::u64().unwrap();
+        assert!(start.elapsed() >= DELAY);
+
+        set_delay(Duration::ZERO);
+        crate::fill(&mut buf).unwrap();
+    }
+}
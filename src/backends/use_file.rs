@@ -6,8 +6,13 @@ use core::{
     sync::atomic::{AtomicI32, Ordering},
 };
 
-#[cfg(not(any(target_os = "android", target_os = "linux")))]
-pub use crate::util::{inner_u32, inner_u64};
+#[cfg(not(any(
+    target_os = "android",
+    target_os = "linux",
+    target_os = "hurd",
+    target_os = "nto"
+)))]
+pub use crate::util::{fill_nonblocking_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
 
 #[path = "../util_libc.rs"]
 pub(super) mod util_libc;
@@ -45,19 +50,60 @@ pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
     if fd == FD_UNINIT || fd == FD_ONGOING_INIT {
         fd = open_or_wait()?;
     }
+    let res = read_exact(fd, dest);
+    #[cfg(getrandom_file_reopen)]
+    {
+        if matches!(res, Err(err) if err.raw_os_error() == Some(libc::EBADF)) {
+            let fd = reopen(fd)?;
+            return read_exact(fd, dest);
+        }
+    }
+    res
+}
+
+/// Open the file (waiting for the RNG to be ready on Linux) without reading from it.
+pub fn init_inner() -> Result<(), Error> {
+    let fd = FD.load(Ordering::Acquire);
+    if fd == FD_UNINIT || fd == FD_ONGOING_INIT {
+        open_or_wait()?;
+    }
+    Ok(())
+}
+
+fn read_exact(fd: libc::c_int, dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    // `read` may be interrupted or return fewer bytes than requested (e.g. on
+    // QNX Neutrino), `sys_fill_exact` retries in both cases.
     util_libc::sys_fill_exact(dest, |buf| unsafe {
         libc::read(fd, buf.as_mut_ptr().cast::<c_void>(), buf.len())
     })
 }
 
+/// Re-open the file after `bad_fd` was closed behind our back (i.e. `read`
+/// failed with `EBADF`), e.g. by code which closes all file descriptors.
+///
+/// Note that this weakens the guarantee that the file is opened only once:
+/// if the closed file descriptor gets reused by another file before it is
+/// detected here, reads will silently go to that file instead. Thus this is
+/// only enabled with `--cfg getrandom_file_reopen`.
+#[cfg(getrandom_file_reopen)]
+#[cold]
+fn reopen(bad_fd: libc::c_int) -> Result<libc::c_int, Error> {
+    // If another thread has already re-opened the file, `FD` no longer
+    // contains `bad_fd` and we use the new file descriptor.
+    let _ = FD.compare_exchange(bad_fd, FD_UNINIT, Ordering::AcqRel, Ordering::Relaxed);
+    open_or_wait()
+}
+
 /// Open a file in read-only mode.
 ///
+/// `path` must be NUL-terminated, e.g. `b"/dev/urandom\0"`.
+///
 /// # Panics
-/// If `path` does not contain any zeros.
+/// If `path` does not contain a NUL byte.
 // TODO: Move `path` to `CStr` and use `CStr::from_bytes_until_nul` (MSRV 1.69)
 // or C-string literals (MSRV 1.77) for statics
 fn open_readonly(path: &[u8]) -> Result<libc::c_int, Error> {
-    assert!(path.iter().any(|&b| b == 0));
+    assert!(path.contains(&0), "path must be NUL-terminated");
     loop {
         let fd = unsafe {
             libc::open(
@@ -230,3 +276,61 @@ mod sync {
         res
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    use super::{fill_inner, MaybeUninit, FD_UNINIT};
+    use super::{init_inner, open_readonly, Ordering, FD};
+
+    #[test]
+    fn test_open_readonly() {
+        let fd = open_readonly(b"/dev/urandom\0").unwrap();
+        assert!(fd >= 0);
+        unsafe { libc::close(fd) };
+    }
+
+    #[test]
+    fn test_init() {
+        init_inner().unwrap();
+        let fd = FD.load(Ordering::Acquire);
+        assert!(fd >= 0);
+        // The file is opened only once
+        init_inner().unwrap();
+        assert_eq!(FD.load(Ordering::Acquire), fd);
+        crate::fill(&mut [0u8; 4]).unwrap();
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    #[test]
+    fn test_init_error() {
+        // Opening the file is made to fail in a child process, so that `FD`
+        // and the file descriptor limit of the test process are not affected.
+        let pid = unsafe { libc::fork() };
+        assert!(pid >= 0);
+        if pid == 0 {
+            FD.store(FD_UNINIT, Ordering::Release);
+            let limit = libc::rlimit {
+                rlim_cur: 0,
+                rlim_max: 0,
+            };
+            unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &limit) };
+            let init_err = init_inner().err();
+            let fill_err = fill_inner(&mut [MaybeUninit::uninit(); 4]).err();
+            let ok = init_err.is_some()
+                && init_err == fill_err
+                && FD.load(Ordering::Acquire) == FD_UNINIT;
+            unsafe { libc::_exit(i32::from(!ok)) };
+        }
+        let mut status = 0;
+        assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+        assert!(libc::WIFEXITED(status));
+        assert_eq!(libc::WEXITSTATUS(status), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "path must be NUL-terminated")]
+    fn test_open_readonly_not_nul_terminated() {
+        let _ = open_readonly(b"/dev/urandom");
+    }
+}
@@ -0,0 +1,19 @@
+//! Backend for formal verification tools (e.g. Kani)
+//!
+//! WARNING: this backend does NOT produce random data! Under Kani every byte
+//! is a nondeterministic value, otherwise buffers are filled with zeros. It
+//! never performs any system calls and MUST be used only for verification.
+use crate::Error;
+use core::mem::MaybeUninit;
+
+pub use crate::util::{fill_nonblocking_inner, init_inner, inner_u32, inner_u64, MAX_FILL_CHUNK};
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    for byte in dest {
+        #[cfg(kani)]
+        byte.write(kani::any());
+        #[cfg(not(kani))]
+        byte.write(0);
+    }
+    Ok(())
+}
@@ -9,7 +9,11 @@ use core::{
 #[path = "../util_libc.rs"]
 mod util_libc;
 
-pub use crate::util::{inner_u32, inner_u64};
+pub use crate::util::{fill_nonblocking_inner, init_inner, inner_u32, inner_u64};
+
+// Prevent overflow of i32
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub const MAX_FILL_CHUNK: usize = i32::MAX as usize;
 
 pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
     static RNG_INIT: AtomicBool = AtomicBool::new(false);
@@ -27,9 +31,7 @@ pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
         }
     }
 
-    // Prevent overflow of i32
-    let chunk_size = usize::try_from(i32::MAX).expect("VxWorks does not support 16-bit targets");
-    for chunk in dest.chunks_mut(chunk_size) {
+    for chunk in dest.chunks_mut(MAX_FILL_CHUNK) {
         let chunk_len: libc::c_int = chunk
             .len()
             .try_into()
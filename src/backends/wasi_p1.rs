@@ -2,7 +2,7 @@
 use crate::Error;
 use core::mem::MaybeUninit;
 
-pub use crate::util::{inner_u32, inner_u64};
+pub use crate::util::{init_inner, inner_u32, inner_u64, MAX_FILL_CHUNK, fill_nonblocking_inner};
 
 // This linking is vendored from the wasi crate:
 // https://docs.rs/wasi/0.11.0+wasi-snapshot-preview1/src/wasi/lib_generated.rs.html#2344-2350
@@ -15,9 +15,11 @@ pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
     // Based on the wasi code:
     // https://docs.rs/wasi/0.11.0+wasi-snapshot-preview1/src/wasi/lib_generated.rs.html#2046-2062
     // Note that size of an allocated object can not be bigger than isize::MAX bytes.
-    // WASI 0.1 supports only 32-bit WASM, so casting length to `i32` is safe.
+    // WASI 0.1 supports only 32-bit WASM, so the length always fits into `i32`,
+    // but we check it explicitly instead of relying on a silent truncation.
+    let len = i32::try_from(dest.len()).map_err(|_| Error::BUFFER_TOO_LARGE)?;
     #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
-    let ret = unsafe { random_get(dest.as_mut_ptr() as i32, dest.len() as i32) };
+    let ret = unsafe { random_get(dest.as_mut_ptr() as i32, len) };
     match ret {
         0 => Ok(()),
         code => {
@@ -28,3 +30,8 @@ pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
         }
     }
 }
+
+impl Error {
+    /// Buffer length can not be represented as `i32`.
+    pub(crate) const BUFFER_TOO_LARGE: Error = Self::new_internal(10);
+}
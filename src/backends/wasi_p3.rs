@@ -0,0 +1,24 @@
+//! Implementation for WASI Preview 3.
+//!
+//! The `wasi:random` interface is synchronous in preview 3 and unchanged
+//! from preview 2, so this backend mirrors the `wasi_p2` one.
+use crate::Error;
+use core::mem::MaybeUninit;
+use wasip3::random::random::get_random_u64;
+
+pub use crate::util::{init_inner, MAX_FILL_CHUNK, fill_nonblocking_inner};
+
+pub fn inner_u32() -> Result<u32, Error> {
+    let val = get_random_u64();
+    Ok(crate::util::truncate(val))
+}
+
+pub fn inner_u64() -> Result<u64, Error> {
+    Ok(get_random_u64())
+}
+
+pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    // See the `wasi_p2` backend for why `get_random_bytes` is not used.
+    crate::util::fill_with_u64(dest, get_random_u64);
+    Ok(())
+}
@@ -2,7 +2,7 @@
 use crate::Error;
 use core::mem::MaybeUninit;
 
-pub use crate::util::{inner_u32, inner_u64};
+pub use crate::util::{fill_nonblocking_inner, init_inner, inner_u32, inner_u64};
 
 #[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
 compile_error!("`wasm_js` backend can be enabled only for OS-less WASM targets!");
@@ -13,14 +13,22 @@ use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
 // See https://developer.mozilla.org/en-US/docs/Web/API/Crypto/getRandomValues
 const MAX_BUFFER_SIZE: usize = 65536;
 
+pub const MAX_FILL_CHUNK: usize = MAX_BUFFER_SIZE;
+
+/// Split `dest` into chunks accepted by `getRandomValues` and fill them with
+/// `fill_chunk`. Larger requests throw `QuotaExceededError`.
+fn fill_chunked(
+    dest: &mut [MaybeUninit<u8>],
+    fill_chunk: impl FnMut(&mut [MaybeUninit<u8>]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    dest.chunks_mut(MAX_BUFFER_SIZE).try_for_each(fill_chunk)
+}
+
 #[cfg(not(target_feature = "atomics"))]
 pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
-    for chunk in dest.chunks_mut(MAX_BUFFER_SIZE) {
-        if get_random_values(chunk).is_err() {
-            return Err(Error::WEB_CRYPTO);
-        }
-    }
-    Ok(())
+    fill_chunked(dest, |chunk| {
+        get_random_values(chunk).map_err(|_| get_random_values_error())
+    })
 }
 
 #[cfg(target_feature = "atomics")]
@@ -32,7 +40,7 @@ pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
         .try_into()
         .expect("buffer length is bounded by MAX_BUFFER_SIZE");
     let buf = js_sys::Uint8Array::new_with_length(buf_len_u32);
-    for chunk in dest.chunks_mut(buf_len) {
+    fill_chunked(dest, |chunk| {
         let chunk_len = chunk
             .len()
             .try_into()
@@ -46,17 +54,34 @@ pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
         };
 
         if get_random_values(sub_buf).is_err() {
-            return Err(Error::WEB_CRYPTO);
+            return Err(get_random_values_error());
         }
 
         // SAFETY: `sub_buf`'s length is the same length as `chunk`
         unsafe { sub_buf.raw_copy_to_ptr(chunk.as_mut_ptr().cast::<u8>()) };
+        Ok(())
+    })
+}
+
+/// Distinguish a missing Web Crypto API from a failing `getRandomValues` call
+#[cold]
+fn get_random_values_error() -> Error {
+    if GLOBAL.with(|global| global.crypto().is_undefined()) {
+        Error::WEB_CRYPTO_UNAVAILABLE
+    } else {
+        Error::WEB_GET_RANDOM_VALUES_FAILED
     }
-    Ok(())
 }
 
 #[wasm_bindgen]
 extern "C" {
+    type Global;
+    #[wasm_bindgen(thread_local_v2, js_name = globalThis)]
+    static GLOBAL: Global;
+    // Read on every call, `globalThis.crypto` can be replaced at runtime
+    #[wasm_bindgen(method, getter)]
+    fn crypto(this: &Global) -> JsValue;
+
     // Crypto.getRandomValues()
     #[cfg(not(target_feature = "atomics"))]
     #[wasm_bindgen(js_namespace = ["globalThis", "crypto"], js_name = getRandomValues, catch)]
@@ -67,6 +92,46 @@ extern "C" {
 }
 
 impl Error {
-    /// The environment does not support the Web Crypto API.
-    pub(crate) const WEB_CRYPTO: Error = Self::new_internal(10);
+    /// The environment does not support the Web Crypto API
+    /// (`globalThis.crypto` is undefined).
+    ///
+    /// Only available with the `wasm_js` backend.
+    pub const WEB_CRYPTO_UNAVAILABLE: Error = Self::new_internal(10);
+    /// Calling `Crypto.getRandomValues` threw an exception.
+    ///
+    /// Only available with the `wasm_js` backend.
+    pub const WEB_GET_RANDOM_VALUES_FAILED: Error = Self::new_internal(11);
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{fill_chunked, MaybeUninit, MAX_BUFFER_SIZE};
+    use std::vec;
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    // Check that no single `getRandomValues` call exceeds its limit
+    #[test]
+    fn test_fill_chunked() {
+        let lens = [
+            0,
+            1,
+            MAX_BUFFER_SIZE,
+            MAX_BUFFER_SIZE + 1,
+            3 * MAX_BUFFER_SIZE + 1,
+        ];
+        for len in lens {
+            let mut buf = vec![MaybeUninit::new(0u8); len];
+            let mut filled = 0;
+            fill_chunked(&mut buf, |chunk| {
+                assert!(!chunk.is_empty());
+                assert!(chunk.len() <= MAX_BUFFER_SIZE);
+                filled += chunk.len();
+                Ok(())
+            })
+            .unwrap();
+            assert_eq!(filled, len);
+        }
+    }
 }
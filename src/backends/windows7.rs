@@ -12,7 +12,11 @@
 use crate::Error;
 use core::{ffi::c_void, mem::MaybeUninit};
 
-pub use crate::util::{inner_u32, inner_u64};
+pub use crate::util::{fill_nonblocking_inner, init_inner, inner_u32, inner_u64};
+
+// Prevent overflow of u32
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub const MAX_FILL_CHUNK: usize = i32::MAX as usize;
 
 // Binding to the Windows.Win32.Security.Authentication.Identity.RtlGenRandom
 // API. Don't use windows-targets as it doesn't support Windows 7 targets.
@@ -26,9 +30,7 @@ type BOOLEAN = u8;
 const TRUE: BOOLEAN = 1u8;
 
 pub fn fill_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
-    // Prevent overflow of u32
-    let chunk_size = usize::try_from(i32::MAX).expect("Windows does not support 16-bit targets");
-    for chunk in dest.chunks_mut(chunk_size) {
+    for chunk in dest.chunks_mut(MAX_FILL_CHUNK) {
         let chunk_len = u32::try_from(chunk.len()).expect("chunk size is bounded by i32::MAX");
         let ret = unsafe { RtlGenRandom(chunk.as_mut_ptr().cast::<c_void>(), chunk_len) };
         if ret != TRUE {
@@ -0,0 +1,146 @@
+//! Best-effort detection of requests made during early boot
+//!
+//! Enabled with `--cfg getrandom_warn_early`. On the first call to
+//! [`fill_uninit`](crate::fill_uninit) the kernel's entropy estimate is read
+//! from `/proc/sys/kernel/random/entropy_avail` and the result is recorded for
+//! [`boot_entropy_was_low`]. The check never fails the request.
+use core::sync::atomic::{AtomicU8, Ordering};
+
+#[cfg(not(all(
+    any(target_os = "linux", target_os = "android"),
+    not(any(target_env = "", getrandom_backend = "custom")),
+)))]
+compile_error!("`getrandom_warn_early` is supported only on Linux and Android targets!");
+
+const ENTROPY_AVAIL_PATH: &[u8] = b"/proc/sys/kernel/random/entropy_avail\0";
+
+/// Entropy estimate (in bits) below which the pool is considered not seeded.
+///
+/// Before Linux 5.18 the CRNG was initialized once the estimate reached 128 bits.
+/// Newer kernels always report 256 bits after initialization.
+const LOW_ENTROPY_THRESHOLD: u32 = 128;
+
+const UNCHECKED: u8 = 0;
+const UNKNOWN: u8 = 1;
+const LOW: u8 = 2;
+const NOT_LOW: u8 = 3;
+
+static STATE: AtomicU8 = AtomicU8::new(UNCHECKED);
+
+/// Returns whether the kernel's entropy estimate was implausibly low at the time
+/// of the first request.
+///
+/// A result of `Some(true)` means that random data was requested before the
+/// kernel's entropy pool was seeded (e.g. a key was generated during early boot).
+/// Returns `None` if no request was made yet or if the estimate could not be read.
+///
+/// Only available with `--cfg getrandom_warn_early` on Linux and Android.
+pub fn boot_entropy_was_low() -> Option<bool> {
+    match STATE.load(Ordering::Relaxed) {
+        LOW => Some(true),
+        NOT_LOW => Some(false),
+        _ => None,
+    }
+}
+
+#[inline]
+pub(crate) fn check_once() {
+    if STATE.load(Ordering::Relaxed) == UNCHECKED {
+        // Concurrent first requests may all run the check, the first result wins.
+        let state = check(ENTROPY_AVAIL_PATH);
+        let _ = STATE.compare_exchange(UNCHECKED, state, Ordering::Relaxed, Ordering::Relaxed);
+    }
+}
+
+#[cold]
+fn check(path: &[u8]) -> u8 {
+    match read_entropy_avail(path) {
+        Some(bits) if bits < LOW_ENTROPY_THRESHOLD => LOW,
+        Some(_) => NOT_LOW,
+        None => UNKNOWN,
+    }
+}
+
+/// Read a decimal value from the NUL-terminated `path`.
+fn read_entropy_avail(path: &[u8]) -> Option<u32> {
+    let fd = unsafe { libc::open(path.as_ptr().cast(), libc::O_RDONLY | libc::O_CLOEXEC) };
+    if fd < 0 {
+        return None;
+    }
+    let mut buf = [0u8; 16];
+    let res = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len()) };
+    unsafe { libc::close(fd) };
+    let len = usize::try_from(res).ok()?;
+    parse_decimal(&buf[..len])
+}
+
+fn parse_decimal(buf: &[u8]) -> Option<u32> {
+    let digits = buf.strip_suffix(b"\n").unwrap_or(buf);
+    if digits.is_empty() {
+        return None;
+    }
+    digits.iter().try_fold(0u32, |acc, &b| {
+        if !b.is_ascii_digit() {
+            return None;
+        }
+        acc.checked_mul(10)?.checked_add(u32::from(b - b'0'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{boot_entropy_was_low, check, read_entropy_avail, STATE};
+    use core::sync::atomic::Ordering;
+    use std::{fs, os::unix::ffi::OsStrExt, vec::Vec};
+
+    fn mock_entropy_avail(name: &str, contents: &[u8]) -> Vec<u8> {
+        let path = std::env::temp_dir().join(name);
+        fs::write(&path, contents).unwrap();
+        let mut path = path.as_os_str().as_bytes().to_vec();
+        path.push(0);
+        path
+    }
+
+    #[test]
+    fn test_read_entropy_avail() {
+        let cases: [(&[u8], Option<u32>); 6] = [
+            (b"42\n", Some(42)),
+            (b"256\n", Some(256)),
+            (b"3", Some(3)),
+            (b"", None),
+            (b"-1\n", None),
+            (b"99999999999\n", None),
+        ];
+        for (i, (contents, expected)) in cases.iter().enumerate() {
+            let name = std::format!("getrandom-entropy-avail-{}-{}", std::process::id(), i);
+            let path = mock_entropy_avail(&name, contents);
+            assert_eq!(read_entropy_avail(&path), *expected, "{:?}", contents);
+            fs::remove_file(std::env::temp_dir().join(name)).unwrap();
+        }
+        assert_eq!(read_entropy_avail(b"/nonexistent/entropy_avail\0"), None);
+    }
+
+    #[test]
+    fn test_check() {
+        let name = std::format!("getrandom-entropy-avail-{}-low", std::process::id());
+        let path = mock_entropy_avail(&name, b"42\n");
+        STATE.store(check(&path), Ordering::Relaxed);
+        assert_eq!(boot_entropy_was_low(), Some(true));
+        fs::remove_file(std::env::temp_dir().join(name)).unwrap();
+
+        let name = std::format!("getrandom-entropy-avail-{}-ok", std::process::id());
+        let path = mock_entropy_avail(&name, b"256\n");
+        STATE.store(check(&path), Ordering::Relaxed);
+        assert_eq!(boot_entropy_was_low(), Some(false));
+        fs::remove_file(std::env::temp_dir().join(name)).unwrap();
+
+        STATE.store(check(b"/nonexistent/entropy_avail\0"), Ordering::Relaxed);
+        assert_eq!(boot_entropy_was_low(), None);
+
+        // Requests don't overwrite a recorded result
+        crate::fill(&mut [0u8; 4]).unwrap();
+        assert_eq!(boot_entropy_was_low(), None);
+    }
+}
@@ -8,6 +8,8 @@ use core::{fmt, num::NonZeroU32};
 cfg_if::cfg_if!(
     if #[cfg(target_os = "uefi")] {
         type RawOsError = usize;
+        /// Bit set in all UEFI status codes which represent errors
+        const UEFI_ERROR_FLAG: usize = 1 << (usize::BITS - 1);
     } else {
         type RawOsError = i32;
     }
@@ -27,9 +29,47 @@ cfg_if::cfg_if!(
 ///   [`std::error::Error`](https://doc.rust-lang.org/std/error/trait.Error.html)
 /// - [`std::io::Error`](https://doc.rust-lang.org/std/io/struct.Error.html) implements
 ///   [`From<getrandom::Error>`](https://doc.rust-lang.org/std/convert/trait.From.html).
+/// - OS errors are described using `std::io::Error`, unless the
+///   `"stable-display"` feature is enabled as well.
+///
+/// *If this crate's `"stable-display"` Cargo feature is enabled*, then OS errors
+/// are displayed using their [symbolic name](Error::os_error_name), e.g.
+/// `EINTR (os error 4)`, which does not depend on the locale or the OS.
+///
+/// On UEFI, the common status codes are always displayed using their symbolic
+/// names, e.g. `EFI_DEVICE_ERROR (os error 9223372036854775815)`.
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub struct Error(NonZeroU32);
 
+// Error wrappers like `anyhow::Error` require `Send + Sync + 'static`
+const _: () = {
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+    let _ = assert_send_sync::<Error>;
+};
+
+/// A broad category of an [`Error`], returned by [`Error::kind()`]
+///
+/// Unlike comparing against associated constants of [`Error`], matching on
+/// this enum does not depend on the numeric encoding of errors or on which
+/// constants are available on the current target.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// See [`Error::UNSUPPORTED`].
+    Unsupported,
+    /// See [`Error::UNEXPECTED`].
+    Unexpected,
+    /// See [`Error::ERRNO_NOT_POSITIVE`].
+    ErrnoNotPositive,
+    /// An error from the OS, see [`Error::raw_os_error()`].
+    Os,
+    /// A custom error, see [`Error::new_custom()`].
+    Custom,
+    /// Any other error reserved for use by the `rand` and `getrandom` crates
+    /// (e.g. a backend-specific failure).
+    Internal,
+}
+
 impl Error {
     /// This target/platform is not supported by `getrandom`.
     pub const UNSUPPORTED: Error = Self::new_internal(0);
@@ -37,6 +77,38 @@ impl Error {
     pub const ERRNO_NOT_POSITIVE: Error = Self::new_internal(1);
     /// Encountered an unexpected situation which should not happen in practice.
     pub const UNEXPECTED: Error = Self::new_internal(2);
+    /// The request could not be completed without blocking.
+    ///
+    /// Returned by the Linux and Android backends to
+    /// [`poll_fill()`](crate::poll_fill) if the kernel entropy pool is not
+    /// initialized yet, and by custom backends which can not fill the buffer
+    /// without blocking. `poll_fill()` reports it as `Poll::Pending`.
+    pub const WOULD_BLOCK: Error = Self::new_internal(3);
+
+    // The RDRAND and RNDR errors are defined here, since several copies of
+    // `rdrand.rs` or `rndr.rs` may be included (e.g. by `chain` and
+    // `getrandom_linux_rdrand_mix`).
+    /// RDRAND instruction failed due to a hardware issue.
+    #[cfg(getrandom_uses_rdrand)]
+    pub(crate) const FAILED_RDRAND: Error = Self::new_internal(10);
+    /// RDRAND instruction unsupported on this target.
+    #[cfg(getrandom_uses_rdrand)]
+    pub(crate) const NO_RDRAND: Error = Self::new_internal(11);
+    /// RNDR register read failed due to a hardware issue.
+    #[cfg(getrandom_uses_rndr)]
+    pub(crate) const RNDR_FAILURE: Error = Self::new_internal(10);
+    /// RNDR register is not supported on this target.
+    #[cfg(getrandom_uses_rndr)]
+    pub(crate) const RNDR_NOT_AVAILABLE: Error = Self::new_internal(11);
+
+    /// UEFI boot services are not available (e.g. after `ExitBootServices`).
+    #[cfg(target_os = "uefi")]
+    #[allow(dead_code)]
+    pub(crate) const BOOT_SERVICES_UNAVAILABLE: Error = Self::new_internal(13);
+    /// No handle supporting a working `EFI_RNG_PROTOCOL` was found.
+    #[cfg(target_os = "uefi")]
+    #[allow(dead_code)]
+    pub(crate) const NO_RNG_HANDLE: Error = Self::new_internal(14);
 
     /// Codes below this point represent OS Errors (i.e. positive i32 values).
     /// Codes at or above this point, but below [`Error::CUSTOM_START`] are
@@ -45,8 +117,16 @@ impl Error {
 
     /// Codes at or above this point can be used by users to define their own
     /// custom errors.
+    ///
+    /// Custom errors should be created with [`Error::new_custom()`], which maps
+    /// `n` to the code `CUSTOM_START + n`.
     pub const CUSTOM_START: u32 = (1 << 31) + (1 << 30);
 
+    /// The largest `n` accepted by [`Error::new_custom()`].
+    ///
+    /// Custom error codes span `0..=CUSTOM_CODE_MAX`, i.e. all `u16` values.
+    pub const CUSTOM_CODE_MAX: u16 = u16::MAX;
+
     /// Creates a new instance of an `Error` from a particular OS error code.
     ///
     /// This method is analogous to [`std::io::Error::from_raw_os_error()`][1],
@@ -64,6 +144,23 @@ impl Error {
         }
     }
 
+    /// Creates a new instance of an `Error` from a UEFI error status code.
+    ///
+    /// The error bit is stripped, so the code can be stored like other OS
+    /// errors. [`Error::raw_os_error()`] restores it. Status codes which are
+    /// not errors are replaced with `Error::UNEXPECTED`.
+    #[cfg(target_os = "uefi")]
+    #[allow(dead_code)]
+    pub(super) fn from_uefi_code(code: RawOsError) -> Self {
+        if code & UEFI_ERROR_FLAG == 0 {
+            return Self::UNEXPECTED;
+        }
+        match u32::try_from(code & !UEFI_ERROR_FLAG) {
+            Ok(code) => Self::from_os_error(code),
+            Err(_) => Self::UNEXPECTED,
+        }
+    }
+
     /// Extract the raw OS error code (if this error came from the OS)
     ///
     /// This method is identical to [`std::io::Error::raw_os_error()`][1], except
@@ -85,16 +182,191 @@ impl Error {
         let errno = RawOsError::try_from(code).ok()?;
         #[cfg(target_os = "solid_asp3")]
         let errno = -errno;
+        #[cfg(target_os = "uefi")]
+        let errno = errno | UEFI_ERROR_FLAG;
         Some(errno)
     }
 
+    /// Extract the raw OS error code (if this error came from the OS) widened to `i64`
+    ///
+    /// Unlike [`Error::raw_os_error()`], the return type of this method is the same
+    /// on all targets (including UEFI, which uses `usize` for OS error codes), which
+    /// makes it convenient for storing errors in a platform-independent way.
+    /// On UEFI the returned value is the status code returned by
+    /// [`Error::raw_os_error()`] (including its error bit) cast to `i64`, so it
+    /// is negative on 64-bit targets.
+    #[inline]
+    pub fn raw_os_error_i64(self) -> Option<i64> {
+        let errno = self.raw_os_error()?;
+        #[cfg(target_os = "uefi")]
+        #[allow(clippy::cast_possible_wrap)]
+        // the error bit is the sign bit on 64-bit targets
+        let errno = errno as i64;
+        #[cfg(not(target_os = "uefi"))]
+        let errno = i64::from(errno);
+        Some(errno)
+    }
+
+    /// Returns the symbolic POSIX name (e.g. `"EAGAIN"`) of the OS error code.
+    ///
+    /// Numeric `errno` values differ between platforms, so the names are more
+    /// convenient for logging. Only the errors which can be returned by this
+    /// crate's backends are named: `EPERM`, `ENOENT`, `EINTR`, `EIO`, `EBADF`,
+    /// `EAGAIN`, `ENOMEM`, `EACCES`, `EFAULT`, `EINVAL`, `ENFILE`, `EMFILE` and
+    /// `ENOSYS`.
+    ///
+    /// On UEFI, the names of the common status codes (e.g. `"EFI_DEVICE_ERROR"`)
+    /// are returned instead.
+    ///
+    /// Returns `None` for other OS errors, for errors which did not come from
+    /// the OS, and on other targets which do not use `errno` values.
+    #[inline]
+    pub fn os_error_name(self) -> Option<&'static str> {
+        self.raw_os_error().and_then(errno_name)
+    }
+
+    /// Check if the error is transient, i.e. if retrying the operation may succeed.
+    ///
+    /// Returns `true` for [`Error::WOULD_BLOCK`], the `EAGAIN` and `EINTR` OS errors
+    /// and hardware RNG failures after exhausting the retry limit (RDRAND, RNDR, Zkr).
+    /// Returns `false` for all other errors, e.g. [`Error::UNSUPPORTED`] or custom errors.
+    pub fn is_retryable(self) -> bool {
+        match self {
+            Error::WOULD_BLOCK => true,
+            #[cfg(getrandom_uses_rdrand)]
+            Error::FAILED_RDRAND => true,
+            #[cfg(getrandom_uses_rndr)]
+            Error::RNDR_FAILURE => true,
+            #[cfg(getrandom_backend = "riscv_zkr")]
+            Error::ZKR_TIMEOUT => true,
+            _ => match self.raw_os_error() {
+                Some(errno) => is_transient_errno(errno),
+                None => false,
+            },
+        }
+    }
+
+    /// Classify this error into a broad [`ErrorKind`].
+    pub fn kind(self) -> ErrorKind {
+        match self {
+            Error::UNSUPPORTED => ErrorKind::Unsupported,
+            Error::UNEXPECTED => ErrorKind::Unexpected,
+            Error::ERRNO_NOT_POSITIVE => ErrorKind::ErrnoNotPositive,
+            _ => match self.0.get() {
+                code if code < Self::INTERNAL_START => ErrorKind::Os,
+                code if code >= Self::CUSTOM_START => ErrorKind::Custom,
+                _ => ErrorKind::Internal,
+            },
+        }
+    }
+
+    /// Returns a short static name of this error's category.
+    ///
+    /// The name depends only on the error code: `"unsupported"`, `"unexpected"`,
+    /// `"errno-not-positive"`, `"os-error"`, `"custom-error"` or `"internal-error"`
+    /// (for all other internal errors). Unlike the `Display` implementation, this
+    /// method does not describe OS errors and can be used in `const` contexts.
+    pub const fn as_str(self) -> &'static str {
+        match self.0.get() {
+            code if code == Self::UNSUPPORTED.0.get() => "unsupported",
+            code if code == Self::UNEXPECTED.0.get() => "unexpected",
+            code if code == Self::ERRNO_NOT_POSITIVE.0.get() => "errno-not-positive",
+            code if code < Self::INTERNAL_START => "os-error",
+            code if code >= Self::CUSTOM_START => "custom-error",
+            _ => "internal-error",
+        }
+    }
+
+    /// Returns an adapter which formats this error with `Debug` using only
+    /// stable fields, e.g. for snapshot tests.
+    ///
+    /// Unlike the `Debug` implementation of `Error`, the output never includes
+    /// descriptions, which may depend on the target, the OS locale or enabled
+    /// features. It consists of the category (`"os"`, `"internal"`, `"custom"`
+    /// or `"unknown"` for codes in the custom range above
+    /// [`Error::CUSTOM_CODE_MAX`]) and the numeric code within that category:
+    /// the OS error code as stored by this crate (see [`Error::into_raw()`]),
+    /// the internal error number, the custom code, or the raw code respectively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use getrandom::Error;
+    ///
+    /// let err = Error::new_custom(7);
+    /// assert_eq!(
+    ///     format!("{:?}", err.debug_stable()),
+    ///     r#"Error { category: "custom", code: 7 }"#,
+    /// );
+    /// ```
+    #[inline]
+    pub fn debug_stable(self) -> impl fmt::Debug {
+        StableDebug(self)
+    }
+
+    /// Returns the raw non-zero code of this error, e.g. for serialization.
+    ///
+    /// The code uses the same encoding on all targets: codes below
+    /// [`Error::INTERNAL_START`] are OS error codes (UEFI status codes are
+    /// stored without their error bit), codes at or above
+    /// [`Error::CUSTOM_START`] are custom errors, and the rest are internal
+    /// errors. [`Error::from_raw()`] restores the exact same error.
+    ///
+    /// Note that the meaning of OS error codes still depends on the target.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use getrandom::Error;
+    ///
+    /// let err = Error::new_custom(7);
+    /// assert_eq!(Error::from_raw(err.into_raw()), Some(err));
+    /// ```
+    #[inline]
+    pub const fn into_raw(self) -> u32 {
+        self.0.get()
+    }
+
+    /// Creates an `Error` from a raw code returned by [`Error::into_raw()`].
+    ///
+    /// Every non-zero code is valid. Returns `None` if `code` is zero.
+    #[inline]
+    pub const fn from_raw(code: u32) -> Option<Error> {
+        match NonZeroU32::new(code) {
+            Some(code) => Some(Error(code)),
+            None => None,
+        }
+    }
+
     /// Creates a new instance of an `Error` from a particular custom error code.
+    ///
+    /// Every `n` in `0..=`[`Error::CUSTOM_CODE_MAX`] is valid and can be
+    /// recovered with [`Error::custom_code()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use getrandom::{Error, ErrorKind};
+    ///
+    /// const HARDWARE_FAULT: Error = Error::new_custom(7);
+    ///
+    /// assert_eq!(HARDWARE_FAULT.kind(), ErrorKind::Custom);
+    /// assert_eq!(HARDWARE_FAULT.custom_code(), Some(7));
+    /// assert_eq!(Error::UNSUPPORTED.custom_code(), None);
+    /// ```
     pub const fn new_custom(n: u16) -> Error {
         // SAFETY: code > 0 as CUSTOM_START > 0 and adding n won't overflow a u32.
         let code = Error::CUSTOM_START + (n as u32);
         Error(unsafe { NonZeroU32::new_unchecked(code) })
     }
 
+    /// Extract the custom error code (if this error was created with
+    /// [`Error::new_custom()`]).
+    pub fn custom_code(self) -> Option<u16> {
+        let n = self.0.get().checked_sub(Self::CUSTOM_START)?;
+        u16::try_from(n).ok()
+    }
+
     /// Creates a new instance of an `Error` from a particular internal error code.
     pub(crate) const fn new_internal(n: u16) -> Error {
         // SAFETY: code > 0 as INTERNAL_START > 0 and adding n won't overflow a u32.
@@ -107,48 +379,191 @@ impl Error {
             Error::UNSUPPORTED => "getrandom: this target is not supported",
             Error::ERRNO_NOT_POSITIVE => "errno: did not return a positive value",
             Error::UNEXPECTED => "unexpected situation",
-            #[cfg(any(
-                target_os = "ios",
-                target_os = "visionos",
-                target_os = "watchos",
-                target_os = "tvos",
+            Error::WOULD_BLOCK => "request would block",
+            #[cfg(feature = "std")]
+            Error::RESERVOIR_EMPTY => "reservoir does not hold enough bytes",
+            Error::REQUEST_TOO_LARGE => "request exceeds the limit set with set_max_fill_len",
+            #[cfg(all(
+                any(
+                    target_os = "ios",
+                    target_os = "visionos",
+                    target_os = "watchos",
+                    target_os = "tvos",
+                ),
+                not(getrandom_apple_getentropy),
             ))]
-            Error::IOS_RANDOM_GEN => "SecRandomCopyBytes: iOS Security framework failure",
+            Error::IOS_RANDOM_GEN => "CCRandomGenerateBytes: CommonCrypto RNG failure",
             #[cfg(all(windows, target_vendor = "win7"))]
             Error::WINDOWS_RTL_GEN_RANDOM => "RtlGenRandom: Windows system function failure",
             #[cfg(getrandom_backend = "wasm_js")]
-            Error::WEB_CRYPTO => "Web Crypto API is unavailable",
+            Error::WEB_CRYPTO_UNAVAILABLE => "Web Crypto API is unavailable",
+            #[cfg(getrandom_backend = "wasm_js")]
+            Error::WEB_GET_RANDOM_VALUES_FAILED => "Crypto.getRandomValues: call failed",
+            #[cfg(getrandom_backend = "mock")]
+            Error::MOCK_EXHAUSTED => "mock backend: all mock bytes have been consumed",
             #[cfg(target_os = "vxworks")]
             Error::VXWORKS_RAND_SECURE => "randSecure: VxWorks RNG module is not initialized",
+            #[cfg(all(target_arch = "wasm32", target_os = "wasi", target_env = "p1"))]
+            Error::BUFFER_TOO_LARGE => "random_get: buffer length does not fit into i32",
 
-            #[cfg(any(
-                getrandom_backend = "rdrand",
-                all(target_arch = "x86_64", target_env = "sgx")
-            ))]
+            #[cfg(getrandom_uses_rdrand)]
             Error::FAILED_RDRAND => "RDRAND: failed multiple times: CPU issue likely",
-            #[cfg(any(
-                getrandom_backend = "rdrand",
-                all(target_arch = "x86_64", target_env = "sgx")
-            ))]
+            #[cfg(getrandom_uses_rdrand)]
             Error::NO_RDRAND => "RDRAND: instruction not supported",
 
-            #[cfg(getrandom_backend = "rndr")]
+            #[cfg(getrandom_uses_rndr)]
             Error::RNDR_FAILURE => "RNDR: Could not generate a random number",
-            #[cfg(getrandom_backend = "rndr")]
+            #[cfg(getrandom_uses_rndr)]
             Error::RNDR_NOT_AVAILABLE => "RNDR: Register not supported",
+            #[cfg(target_os = "uefi")]
+            Error::BOOT_SERVICES_UNAVAILABLE => "EFI: boot services are unavailable",
+            #[cfg(target_os = "uefi")]
+            Error::NO_RNG_HANDLE => "EFI: no working RNG protocol handle was found",
+            #[cfg(getrandom_backend = "dual_check")]
+            Error::STUCK_SOURCE => "health test: source returned the same sample twice",
+            #[cfg(getrandom_backend = "hybrid_hw_os")]
+            Error::HW_MATCHES_OS => "cross-check: hardware RNG returned the same sample as the OS",
+            #[cfg(getrandom_backend = "aesni_drbg")]
+            Error::NO_AESNI => "AES-NI: instructions not supported",
+            #[cfg(getrandom_backend = "riscv_zkr")]
+            Error::ZKR_DEAD => "Zkr: entropy source is dead (self-test failure)",
+            #[cfg(getrandom_backend = "riscv_zkr")]
+            Error::ZKR_TIMEOUT => "Zkr: entropy source is not ready after multiple retries",
             _ => return None,
         };
         Some(desc)
     }
 }
 
+cfg_if! {
+    // Targets which depend on `libc`, see `Cargo.toml`
+    if #[cfg(any(
+        all(
+            any(target_os = "linux", target_os = "android"),
+            not(any(target_env = "", getrandom_backend = "custom")),
+        ),
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "visionos",
+        target_os = "watchos",
+        target_os = "tvos",
+        target_os = "openbsd",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "dragonfly",
+        target_os = "illumos",
+        target_os = "solaris",
+        target_os = "hurd",
+        target_os = "haiku",
+        target_os = "redox",
+        target_os = "nto",
+        target_os = "aix",
+    ))] {
+        /// Check if the errno value indicates a transient failure.
+        fn is_transient_errno(errno: RawOsError) -> bool {
+            errno == libc::EAGAIN || errno == libc::EINTR
+        }
+
+        /// Describe the errno values which can be returned by this crate's backends
+        /// (used instead of `std::io::Error` when the `std` feature is disabled).
+        #[cfg(not(any(feature = "std", feature = "stable-display")))]
+        fn errno_desc(errno: RawOsError) -> Option<&'static str> {
+            let desc = match errno {
+                libc::EPERM => "Operation not permitted",
+                libc::ENOENT => "No such file or directory",
+                libc::EINTR => "Interrupted system call",
+                libc::EIO => "Input/output error",
+                libc::EBADF => "Bad file descriptor",
+                libc::EAGAIN => "Resource temporarily unavailable",
+                libc::ENOMEM => "Cannot allocate memory",
+                libc::EACCES => "Permission denied",
+                libc::EFAULT => "Bad address",
+                libc::EINVAL => "Invalid argument",
+                libc::ENFILE => "Too many open files in system",
+                libc::EMFILE => "Too many open files",
+                libc::ENOSYS => "Function not implemented",
+                _ => return None,
+            };
+            Some(desc)
+        }
+
+        /// Name the errno values which can be returned by this crate's backends
+        fn errno_name(errno: RawOsError) -> Option<&'static str> {
+            let name = match errno {
+                libc::EPERM => "EPERM",
+                libc::ENOENT => "ENOENT",
+                libc::EINTR => "EINTR",
+                libc::EIO => "EIO",
+                libc::EBADF => "EBADF",
+                libc::EAGAIN => "EAGAIN",
+                libc::ENOMEM => "ENOMEM",
+                libc::EACCES => "EACCES",
+                libc::EFAULT => "EFAULT",
+                libc::EINVAL => "EINVAL",
+                libc::ENFILE => "ENFILE",
+                libc::EMFILE => "EMFILE",
+                libc::ENOSYS => "ENOSYS",
+                _ => return None,
+            };
+            Some(name)
+        }
+    } else if #[cfg(target_os = "uefi")] {
+        fn is_transient_errno(_errno: RawOsError) -> bool {
+            false
+        }
+
+        /// Name the UEFI status codes which are relevant for random number generation
+        fn errno_name(status: RawOsError) -> Option<&'static str> {
+            let name = match status & !UEFI_ERROR_FLAG {
+                1 => "EFI_LOAD_ERROR",
+                2 => "EFI_INVALID_PARAMETER",
+                3 => "EFI_UNSUPPORTED",
+                5 => "EFI_BUFFER_TOO_SMALL",
+                6 => "EFI_NOT_READY",
+                7 => "EFI_DEVICE_ERROR",
+                9 => "EFI_OUT_OF_RESOURCES",
+                14 => "EFI_NOT_FOUND",
+                15 => "EFI_ACCESS_DENIED",
+                18 => "EFI_TIMEOUT",
+                21 => "EFI_ABORTED",
+                _ => return None,
+            };
+            Some(name)
+        }
+    } else {
+        fn is_transient_errno(_errno: RawOsError) -> bool {
+            false
+        }
+
+        fn errno_name(_errno: RawOsError) -> Option<&'static str> {
+            None
+        }
+
+        #[cfg(not(any(feature = "std", feature = "stable-display")))]
+        fn errno_desc(_errno: RawOsError) -> Option<&'static str> {
+            None
+        }
+    }
+}
+
 impl fmt::Debug for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut dbg = f.debug_struct("Error");
         if let Some(errno) = self.raw_os_error() {
             dbg.field("os_error", &errno);
-            #[cfg(feature = "std")]
-            dbg.field("description", &std::io::Error::from_raw_os_error(errno));
+            cfg_if! {
+                if #[cfg(any(feature = "stable-display", target_os = "uefi"))] {
+                    if let Some(name) = errno_name(errno) {
+                        dbg.field("name", &name);
+                    }
+                } else if #[cfg(feature = "std")] {
+                    dbg.field("description", &std::io::Error::from_raw_os_error(errno));
+                } else {
+                    if let Some(desc) = errno_desc(errno) {
+                        dbg.field("description", &desc);
+                    }
+                }
+            }
         } else if let Some(desc) = self.internal_desc() {
             dbg.field("internal_code", &self.0.get());
             dbg.field("description", &desc);
@@ -159,14 +574,71 @@ impl fmt::Debug for Error {
     }
 }
 
+/// `Debug` adapter returned by [`Error::debug_stable()`]
+struct StableDebug(Error);
+
+impl fmt::Debug for StableDebug {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = self.0 .0.get();
+        let (category, code) = if code < Error::INTERNAL_START {
+            ("os", code)
+        } else if code < Error::CUSTOM_START {
+            ("internal", code - Error::INTERNAL_START)
+        } else if let Some(n) = self.0.custom_code() {
+            ("custom", u32::from(n))
+        } else {
+            ("unknown", code)
+        };
+        f.debug_struct("Error")
+            .field("category", &category)
+            .field("code", &code)
+            .finish()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Error {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        if let Some(errno) = self.raw_os_error() {
+            defmt::write!(f, "OS Error: {}", errno);
+        } else if let Some(desc) = self.internal_desc() {
+            defmt::write!(f, "Internal Error {}: {=str}", self.0.get(), desc);
+        } else {
+            defmt::write!(f, "Unknown Error: {}", self.0.get());
+        }
+    }
+}
+
+#[cfg(feature = "rustix")]
+impl From<rustix::io::Errno> for Error {
+    /// Convert an error returned by `rustix` into an OS error.
+    ///
+    /// Non-positive codes are mapped to [`Error::ERRNO_NOT_POSITIVE`].
+    fn from(errno: rustix::io::Errno) -> Self {
+        match u32::try_from(errno.raw_os_error()) {
+            Ok(code) if code > 0 => Error::from_os_error(code),
+            _ => Error::ERRNO_NOT_POSITIVE,
+        }
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if let Some(errno) = self.raw_os_error() {
             cfg_if! {
-                if #[cfg(feature = "std")] {
+                // Unlike `errno` values, UEFI status codes are not meaningful as numbers
+                if #[cfg(any(feature = "stable-display", target_os = "uefi"))] {
+                    match errno_name(errno) {
+                        Some(name) => write!(f, "{} (os error {})", name, errno),
+                        None => write!(f, "OS Error: {}", errno),
+                    }
+                } else if #[cfg(feature = "std")] {
                     std::io::Error::from_raw_os_error(errno).fmt(f)
                 } else {
-                    write!(f, "OS Error: {}", errno)
+                    match errno_desc(errno) {
+                        Some(desc) => write!(f, "{} (os error {})", desc, errno),
+                        None => write!(f, "OS Error: {}", errno),
+                    }
                 }
             }
         } else if let Some(desc) = self.internal_desc() {
@@ -179,7 +651,7 @@ impl fmt::Display for Error {
 
 #[cfg(test)]
 mod tests {
-    use super::Error;
+    use super::{Error, ErrorKind};
     use core::mem::size_of;
 
     #[test]
@@ -187,4 +659,271 @@ mod tests {
         assert_eq!(size_of::<Error>(), 4);
         assert_eq!(size_of::<Result<(), Error>>(), 4);
     }
+
+    #[test]
+    fn test_raw_os_error_i64() {
+        let err = Error::from_os_error(5);
+        #[cfg(not(target_os = "uefi"))]
+        assert_eq!(err.raw_os_error_i64(), Some(5));
+        // UEFI status codes keep their error bit, like in `raw_os_error()`
+        #[cfg(target_os = "uefi")]
+        #[allow(clippy::cast_possible_wrap)]
+        let expected = (5 | super::UEFI_ERROR_FLAG) as i64;
+        #[cfg(target_os = "uefi")]
+        assert_eq!(err.raw_os_error_i64(), Some(expected));
+
+        assert_eq!(Error::UNEXPECTED.raw_os_error_i64(), None);
+        assert_eq!(Error::new_custom(5).raw_os_error_i64(), None);
+    }
+
+    #[test]
+    fn test_kind() {
+        assert_eq!(Error::UNSUPPORTED.kind(), ErrorKind::Unsupported);
+        assert_eq!(Error::UNEXPECTED.kind(), ErrorKind::Unexpected);
+        assert_eq!(
+            Error::ERRNO_NOT_POSITIVE.kind(),
+            ErrorKind::ErrnoNotPositive
+        );
+        assert_eq!(Error::from_os_error(1).kind(), ErrorKind::Os);
+        assert_eq!(
+            Error::from_os_error(Error::INTERNAL_START - 1).kind(),
+            ErrorKind::Os
+        );
+        assert_eq!(Error::WOULD_BLOCK.kind(), ErrorKind::Internal);
+        assert_eq!(Error::new_internal(u16::MAX).kind(), ErrorKind::Internal);
+        assert_eq!(Error::new_custom(0).kind(), ErrorKind::Custom);
+        assert_eq!(Error::new_custom(u16::MAX).kind(), ErrorKind::Custom);
+    }
+
+    #[test]
+    fn test_custom_code() {
+        for n in [0, 1, 42, Error::CUSTOM_CODE_MAX] {
+            assert_eq!(Error::new_custom(n).custom_code(), Some(n));
+        }
+        assert_eq!(Error::UNSUPPORTED.custom_code(), None);
+        assert_eq!(Error::new_internal(u16::MAX).custom_code(), None);
+        assert_eq!(Error::from_os_error(1).custom_code(), None);
+    }
+
+    #[test]
+    fn test_raw_round_trip() {
+        let errors = [
+            Error::from_os_error(1),
+            Error::from_os_error(Error::INTERNAL_START - 1),
+            Error::UNSUPPORTED,
+            Error::WOULD_BLOCK,
+            Error::new_internal(u16::MAX),
+            Error::new_custom(0),
+            Error::new_custom(Error::CUSTOM_CODE_MAX),
+            #[cfg(target_os = "uefi")]
+            Error::from_uefi_code(super::UEFI_ERROR_FLAG | 3),
+        ];
+        for err in errors {
+            let raw = err.into_raw();
+            assert_ne!(raw, 0);
+            assert_eq!(Error::from_raw(raw), Some(err));
+            assert_eq!(
+                Error::from_raw(raw).unwrap().raw_os_error(),
+                err.raw_os_error()
+            );
+        }
+        assert_eq!(Error::from_raw(0), None);
+        assert_eq!(
+            Error::from_raw(u32::MAX).map(Error::kind),
+            Some(ErrorKind::Custom)
+        );
+    }
+
+    #[test]
+    fn test_as_str() {
+        const UNSUPPORTED: &str = Error::UNSUPPORTED.as_str();
+        assert_eq!(UNSUPPORTED, "unsupported");
+        assert_eq!(Error::UNEXPECTED.as_str(), "unexpected");
+        assert_eq!(Error::ERRNO_NOT_POSITIVE.as_str(), "errno-not-positive");
+        assert_eq!(Error::from_os_error(1).as_str(), "os-error");
+        assert_eq!(
+            Error::from_os_error(Error::INTERNAL_START - 1).as_str(),
+            "os-error"
+        );
+        assert_eq!(Error::WOULD_BLOCK.as_str(), "internal-error");
+        assert_eq!(Error::new_internal(u16::MAX).as_str(), "internal-error");
+        assert_eq!(Error::new_custom(0).as_str(), "custom-error");
+        assert_eq!(Error::new_custom(u16::MAX).as_str(), "custom-error");
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        let errors = [
+            (Error::UNSUPPORTED, false),
+            (Error::ERRNO_NOT_POSITIVE, false),
+            (Error::UNEXPECTED, false),
+            (Error::WOULD_BLOCK, true),
+            (Error::new_custom(0), false),
+            #[cfg(getrandom_backend = "rdrand")]
+            (Error::FAILED_RDRAND, true),
+            #[cfg(getrandom_backend = "rdrand")]
+            (Error::NO_RDRAND, false),
+            #[cfg(getrandom_backend = "rndr")]
+            (Error::RNDR_FAILURE, true),
+            #[cfg(getrandom_backend = "rndr")]
+            (Error::RNDR_NOT_AVAILABLE, false),
+            #[cfg(getrandom_backend = "riscv_zkr")]
+            (Error::ZKR_TIMEOUT, true),
+            #[cfg(getrandom_backend = "riscv_zkr")]
+            (Error::ZKR_DEAD, false),
+        ];
+        for (err, retryable) in errors {
+            assert_eq!(err.is_retryable(), retryable, "{:?}", err);
+        }
+    }
+
+    #[cfg(all(
+        any(target_os = "linux", target_os = "macos"),
+        not(getrandom_backend = "custom")
+    ))]
+    #[test]
+    fn test_is_retryable_errno() {
+        let errnos = [
+            (libc::EAGAIN, true),
+            (libc::EINTR, true),
+            (libc::ENOSYS, false),
+            (libc::EFAULT, false),
+            (libc::EPERM, false),
+        ];
+        for (errno, retryable) in errnos {
+            let err = Error::from_os_error(errno.unsigned_abs());
+            assert_eq!(err.is_retryable(), retryable, "{:?}", err);
+        }
+    }
+
+    #[cfg(all(
+        any(target_os = "linux", target_os = "macos"),
+        not(getrandom_backend = "custom")
+    ))]
+    #[test]
+    fn test_os_error_name() {
+        let errnos = [
+            (libc::EAGAIN, "EAGAIN"),
+            (libc::EINTR, "EINTR"),
+            (libc::ENOSYS, "ENOSYS"),
+            (libc::EFAULT, "EFAULT"),
+            (libc::EPERM, "EPERM"),
+            (libc::EIO, "EIO"),
+            (libc::EINVAL, "EINVAL"),
+        ];
+        for (errno, name) in errnos {
+            let err = Error::from_os_error(errno.unsigned_abs());
+            assert_eq!(err.os_error_name(), Some(name), "{:?}", err);
+        }
+        assert_eq!(
+            Error::from_os_error(libc::EDOM.unsigned_abs()).os_error_name(),
+            None
+        );
+        assert_eq!(Error::UNSUPPORTED.os_error_name(), None);
+        assert_eq!(Error::new_custom(1).os_error_name(), None);
+    }
+
+    #[cfg(all(
+        not(any(feature = "std", feature = "stable-display")),
+        any(target_os = "linux", target_os = "macos"),
+        not(getrandom_backend = "custom")
+    ))]
+    #[test]
+    fn test_errno_display() {
+        extern crate std;
+        use std::string::ToString;
+
+        let err = Error::from_os_error(libc::EINTR.unsigned_abs());
+        let expected = std::format!("Interrupted system call (os error {})", libc::EINTR);
+        assert_eq!(err.to_string(), expected);
+    }
+
+    #[cfg(all(
+        feature = "stable-display",
+        any(target_os = "linux", target_os = "macos"),
+        not(getrandom_backend = "custom")
+    ))]
+    #[test]
+    fn test_stable_display() {
+        extern crate std;
+        use std::{format, string::ToString};
+
+        let err = Error::from_os_error(libc::EINTR.unsigned_abs());
+        assert_eq!(err.to_string(), format!("EINTR (os error {})", libc::EINTR));
+        assert_eq!(
+            format!("{:?}", err),
+            format!("Error {{ os_error: {}, name: \"EINTR\" }}", libc::EINTR)
+        );
+
+        let err = Error::from_os_error(libc::EDOM.unsigned_abs());
+        assert_eq!(err.to_string(), format!("OS Error: {}", libc::EDOM));
+    }
+
+    #[cfg(target_os = "uefi")]
+    #[test]
+    fn test_uefi_status_names() {
+        extern crate std;
+        use super::UEFI_ERROR_FLAG;
+        use std::{format, string::ToString};
+
+        let err = Error::from_uefi_code(UEFI_ERROR_FLAG | 7);
+        assert_eq!(err.os_error_name(), Some("EFI_DEVICE_ERROR"));
+        let status = UEFI_ERROR_FLAG | 7;
+        assert_eq!(
+            err.to_string(),
+            format!("EFI_DEVICE_ERROR (os error {})", status)
+        );
+        assert_eq!(
+            format!("{:?}", err),
+            format!(
+                "Error {{ os_error: {}, name: \"EFI_DEVICE_ERROR\" }}",
+                status
+            )
+        );
+
+        let err = Error::from_uefi_code(UEFI_ERROR_FLAG | 6);
+        assert_eq!(err.os_error_name(), Some("EFI_NOT_READY"));
+
+        // Unknown status codes are displayed as numbers
+        let status = UEFI_ERROR_FLAG | 30;
+        let err = Error::from_uefi_code(status);
+        assert_eq!(err.os_error_name(), None);
+        assert_eq!(err.to_string(), format!("OS Error: {}", status));
+    }
+
+    #[test]
+    fn test_debug_stable() {
+        extern crate std;
+        use std::format;
+
+        let errors = [
+            (
+                Error::from_os_error(4),
+                r#"Error { category: "os", code: 4 }"#,
+            ),
+            (
+                Error::UNSUPPORTED,
+                r#"Error { category: "internal", code: 0 }"#,
+            ),
+            (
+                Error::REQUEST_TOO_LARGE,
+                r#"Error { category: "internal", code: 5 }"#,
+            ),
+            (
+                Error::new_internal(u16::MAX),
+                r#"Error { category: "internal", code: 65535 }"#,
+            ),
+            (
+                Error::new_custom(7),
+                r#"Error { category: "custom", code: 7 }"#,
+            ),
+            (
+                Error::from_raw(u32::MAX).unwrap(),
+                r#"Error { category: "unknown", code: 4294967295 }"#,
+            ),
+        ];
+        for (err, expected) in errors {
+            assert_eq!(format!("{:?}", err.debug_stable()), expected);
+        }
+    }
 }
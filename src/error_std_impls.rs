@@ -12,4 +12,25 @@ impl From<Error> for io::Error {
     }
 }
 
+/// Wrapped errors (e.g. in `anyhow::Error` or `eyre::Report`, which require
+/// `Send + Sync + 'static`) can be recovered with a downcast and classified
+/// with [`Error::kind()`]:
+///
+/// ```
+/// use getrandom::{Error, ErrorKind};
+///
+/// type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
+///
+/// fn generate_key() -> Result<[u8; 32], BoxError> {
+///     // Simulate a failure of the underlying `getrandom::fill` call
+///     Err(Error::UNSUPPORTED.into())
+/// }
+///
+/// let err = generate_key().unwrap_err();
+/// match err.downcast_ref::<Error>().map(|err| err.kind()) {
+///     Some(ErrorKind::Unsupported) => println!("no RNG on this target"),
+///     Some(kind) => println!("RNG failure: {:?}", kind),
+///     None => println!("other failure: {}", err),
+/// }
+/// ```
 impl std::error::Error for Error {}
@@ -0,0 +1,41 @@
+//! C ABI entry point, available with the `ffi` feature
+use core::{mem::MaybeUninit, slice};
+
+/// Fill `len` bytes starting at `ptr` with random bytes (C ABI).
+///
+/// Returns 0 on success. On failure, returns the non-zero code of the
+/// [`Error`](crate::Error) as returned by [`Error::into_raw()`](crate::Error::into_raw):
+/// codes below [`Error::INTERNAL_START`](crate::Error::INTERNAL_START)
+/// are OS error codes, codes at or above [`Error::CUSTOM_START`](crate::Error::CUSTOM_START)
+/// are custom errors, and the rest are internal errors.
+///
+/// The corresponding C declaration is:
+/// ```c
+/// #include <stddef.h>
+/// #include <stdint.h>
+///
+/// uint32_t getrandom_fill(uint8_t *ptr, size_t len);
+/// ```
+/// (it can also be generated from this crate with tools like [`cbindgen`]).
+///
+/// This function does not panic. Note that since it uses the `extern "C"` ABI,
+/// a panic inside of it (e.g. in a custom backend) would abort the process.
+///
+/// # Safety
+///
+/// `ptr` must be valid for writes of `len` bytes. The memory may be
+/// uninitialized. If `len` is 0, `ptr` may be null.
+///
+/// [`cbindgen`]: https://github.com/mozilla/cbindgen
+#[no_mangle]
+pub unsafe extern "C" fn getrandom_fill(ptr: *mut u8, len: usize) -> u32 {
+    if len == 0 {
+        return 0;
+    }
+    // SAFETY: the caller guarantees that `ptr` is valid for writes of `len` bytes
+    let dest = unsafe { slice::from_raw_parts_mut(ptr.cast::<MaybeUninit<u8>>(), len) };
+    match crate::fill_uninit(dest) {
+        Ok(_) => 0,
+        Err(err) => err.into_raw(),
+    }
+}
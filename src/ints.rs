@@ -0,0 +1,24 @@
+//! Integer types which can be filled with [`fill_ints`](crate::fill_ints)
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Primitive integer types which can be filled with random bits by
+/// [`fill_ints`](crate::fill_ints).
+///
+/// The trait is sealed and implemented only for types without padding for
+/// which every bit pattern is a valid value, so that a slice of them can be
+/// safely filled as bytes.
+pub trait IntFill: sealed::Sealed + Copy {}
+
+macro_rules! impl_int_fill {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl sealed::Sealed for $t {}
+            impl IntFill for $t {}
+        )*
+    };
+}
+
+impl_int_fill!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
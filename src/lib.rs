@@ -10,6 +10,10 @@
 #![doc = include_str!("../README.md")]
 #![warn(rust_2018_idioms, unused_lifetimes, missing_docs)]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
+#![cfg_attr(getrandom_std_random, feature(random))]
+// The `efi_rng` backend (the default on UEFI) and `efi_rng_with_fallback` use the
+// unstable `std::os::uefi`, see `build.rs`.
+#![cfg_attr(getrandom_uefi_std, feature(uefi_std))]
 #![deny(
     clippy::cast_lossless,
     clippy::cast_possible_truncation,
@@ -28,17 +32,83 @@
 
 #[macro_use]
 extern crate cfg_if;
+#[cfg(feature = "std")]
+extern crate std;
 
-use core::mem::MaybeUninit;
+use core::{mem::MaybeUninit, task::Poll};
 
 mod backends;
 mod error;
 mod util;
 
+#[cfg(getrandom_warn_early)]
+mod boot_entropy;
+
 #[cfg(feature = "std")]
 mod error_std_impls;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod ints;
+mod max_fill_len;
+#[cfg(feature = "observer")]
+mod observer;
+#[cfg(feature = "std")]
+mod reservoir;
+#[cfg(target_os = "fuchsia")]
+mod zircon;
 
-pub use crate::error::Error;
+pub use crate::error::{Error, ErrorKind};
+#[cfg(feature = "ffi")]
+pub use crate::ffi::getrandom_fill;
+pub use crate::ints::IntFill;
+pub use crate::max_fill_len::set_max_fill_len;
+#[cfg(feature = "observer")]
+pub use crate::observer::{clear_observer, set_observer, Observer};
+#[cfg(feature = "std")]
+pub use crate::reservoir::Reservoir;
+#[cfg(target_os = "fuchsia")]
+pub use crate::zircon::zx_cprng_draw;
+
+#[cfg(getrandom_warn_early)]
+pub use crate::boot_entropy::boot_entropy_was_low;
+#[cfg(getrandom_backend = "counter")]
+pub use backends::reset_counter;
+#[cfg(getrandom_backend = "test_slow")]
+pub use backends::set_delay;
+#[cfg(getrandom_backend = "mock")]
+pub use backends::set_mock_bytes;
+#[cfg(getrandom_backend = "test_failing")]
+pub use backends::set_test_error;
+#[cfg(getrandom_backend = "proxy")]
+pub use backends::{clear_proxy, set_proxy, ProxyFn};
+
+/// Maximum number of bytes the active backend requests from the system
+/// in a single call.
+///
+/// This is a per-call cap, not a limit on the total length: [`fill`] and
+/// [`fill_uninit`] accept buffers of any size and split longer requests
+/// into multiple calls internally. Code which does its own chunking (e.g.
+/// to report progress or to check for cancellation) can use this value so
+/// that every call to [`fill`] maps onto a single call to the system.
+///
+/// Backends without such a limit use `usize::MAX`.
+pub const MAX_FILL_CHUNK: usize = backends::MAX_FILL_CHUNK;
+
+/// Version of the ABI used to call the custom backend.
+///
+/// The symbol of the custom backend function includes the ABI version, so that
+/// an implementation written for a different major version of `getrandom` results
+/// in a linker error instead of being silently called with an incompatible signature:
+///
+/// | `getrandom` | ABI version | Symbol                   |
+/// | ----------- | ----------- | ------------------------ |
+/// | v0.2        | 2           | `__getrandom_custom` (defined by `register_custom_getrandom!`) |
+/// | v0.3        | 3           | `__getrandom_v03_custom` |
+///
+/// An "undefined symbol" linker error which mentions one of these symbols usually
+/// means that the custom backend was enabled, but its implementation was written
+/// for a different version of `getrandom` (or is missing entirely).
+pub const BACKEND_ABI_VERSION: u32 = 3;
 
 /// Fill `dest` with random bytes from the system's preferred random number source.
 ///
@@ -53,6 +123,11 @@ pub use crate::error::Error;
 /// significantly slower than a user-space CSPRNG; for the latter consider
 /// [`rand::thread_rng`](https://docs.rs/rand/*/rand/fn.thread_rng.html).
 ///
+/// Ignoring the returned error is almost always a bug, since `dest` may not
+/// be random afterwards. In the rare cases where this is intended (e.g. when
+/// the bytes are only mixed into an existing entropy pool), discard the
+/// result explicitly with `let _ = getrandom::fill(&mut buf);`.
+///
 /// # Examples
 ///
 /// ```
@@ -61,6 +136,7 @@ pub use crate::error::Error;
 /// getrandom::fill(&mut buf)?;
 /// # Ok(()) }
 /// ```
+#[must_use = "random fill may have failed; the buffer contents are unspecified on error"]
 #[inline]
 pub fn fill(dest: &mut [u8]) -> Result<(), Error> {
     // SAFETY: The `&mut MaybeUninit<_>` reference doesn't escape,
@@ -70,6 +146,137 @@ pub fn fill(dest: &mut [u8]) -> Result<(), Error> {
     Ok(())
 }
 
+/// Fill `dest` with random bytes, reporting a source which would block as
+/// [`Poll::Pending`].
+///
+/// This function is equivalent to [`fill`], except that [`Error::WOULD_BLOCK`]
+/// is returned as `Poll::Pending` instead of `Poll::Ready(Err(_))`. It allows
+/// building async wrappers on top of this crate without a runtime dependency.
+///
+/// Note that `Poll::Pending` does not register a waker: the caller is
+/// responsible for polling again later (e.g. after a timer fires). The
+/// contents of `dest` are unspecified unless `Poll::Ready(Ok(()))` is returned.
+///
+/// On Linux and Android, `getrandom(2)` is called with `GRND_NONBLOCK`, so
+/// `Poll::Pending` is returned while the kernel entropy pool is not
+/// initialized (with the `/dev/urandom` fallback the call still blocks). The
+/// other backends for operating systems block until the source is ready (see
+/// the module documentation), so they always return `Poll::Ready`. Custom
+/// backends can return [`Error::WOULD_BLOCK`] instead of blocking.
+///
+/// # Examples
+///
+/// ```
+/// use core::task::Poll;
+///
+/// let mut buf = [0u8; 32];
+/// match getrandom::poll_fill(&mut buf) {
+///     Poll::Ready(Ok(())) => { /* use `buf` */ }
+///     Poll::Ready(Err(err)) => panic!("getrandom failed: {}", err),
+///     Poll::Pending => { /* try again later */ }
+/// }
+/// ```
+#[inline]
+pub fn poll_fill(dest: &mut [u8]) -> Poll<Result<(), Error>> {
+    #[cfg(getrandom_warn_early)]
+    boot_entropy::check_once();
+    let len = dest.len();
+    let res = max_fill_len::check(len).and_then(|()| {
+        if len == 0 {
+            return Ok(());
+        }
+        // SAFETY: `fill_nonblocking_inner` never writes uninitialized bytes
+        backends::fill_nonblocking_inner(unsafe { util::slice_as_uninit_mut(dest) })
+    });
+    #[cfg(feature = "observer")]
+    observer::notify(len, &res);
+    match res {
+        Err(Error::WOULD_BLOCK) => Poll::Pending,
+        res => Poll::Ready(res),
+    }
+}
+
+/// Fill the array `arr` with random bytes.
+///
+/// This function is equivalent to [`fill`], but takes an array reference
+/// directly, so that no unsizing coercion to `&mut [u8]` is necessary (e.g.
+/// for `&mut Box<[u8; N]>` or in generic code).
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), getrandom::Error> {
+/// let mut key = Box::new([0u8; 16]);
+/// getrandom::fill_array_mut(&mut key)?;
+/// # Ok(()) }
+/// ```
+#[must_use = "random fill may have failed; the buffer contents are unspecified on error"]
+#[inline]
+pub fn fill_array_mut<const N: usize>(arr: &mut [u8; N]) -> Result<(), Error> {
+    fill(&mut arr[..])
+}
+
+/// Fill the slice of integers `dst` with random bits.
+///
+/// This function is equivalent to calling [`fill`] on the memory of `dst`,
+/// so each integer is made of native-endian bytes. This does not matter for
+/// randomness, but the same bytes from the backend (e.g. with a deterministic
+/// backend) result in different values on little- and big-endian targets.
+/// Use [`fill`] with `from_le_bytes` if the values must be reproducible
+/// across targets.
+///
+/// Every value is completely overwritten, and since `dst` is a slice of
+/// integers it is always properly aligned for them.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), getrandom::Error> {
+/// let mut samples = [0u16; 64];
+/// getrandom::fill_ints(&mut samples)?;
+/// let mut offsets = [0i64; 8];
+/// getrandom::fill_ints(&mut offsets)?;
+/// # Ok(()) }
+/// ```
+#[must_use = "random fill may have failed; the buffer contents are unspecified on error"]
+#[inline]
+pub fn fill_ints<T: IntFill>(dst: &mut [T]) -> Result<(), Error> {
+    let len = core::mem::size_of_val(dst);
+    // SAFETY: `IntFill` is only implemented for primitive integers, which have
+    // no padding and for which every bit pattern is valid, so `dst` can be
+    // viewed as `len` initialized bytes.
+    let bytes = unsafe { core::slice::from_raw_parts_mut(dst.as_mut_ptr().cast::<u8>(), len) };
+    fill(bytes)
+}
+
+/// Fill `dest` with random bytes, zeroing it if an error occurs.
+///
+/// This function behaves like [`fill`], except that on error `dest` is
+/// overwritten with zeros before the error is returned. The zeroing uses
+/// volatile writes, so it can not be optimized away even if `dest` is
+/// never read again. It is intended for buffers which hold secrets (e.g.
+/// keys), so that no partially written data remains in them on failure.
+///
+/// On success, `dest` is filled with random bytes exactly as by [`fill`].
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), getrandom::Error> {
+/// let mut key = [0u8; 32];
+/// getrandom::fill_scrub_on_error(&mut key)?;
+/// # Ok(()) }
+/// ```
+#[must_use = "random fill may have failed; the buffer contents are unspecified on error"]
+#[inline]
+pub fn fill_scrub_on_error(dest: &mut [u8]) -> Result<(), Error> {
+    let res = fill(dest);
+    if res.is_err() {
+        util::volatile_zero(dest);
+    }
+    res
+}
+
 /// Fill potentially uninitialized buffer `dest` with random bytes from
 /// the system's preferred random number source and return a mutable
 /// reference to those bytes.
@@ -92,8 +299,24 @@ pub fn fill(dest: &mut [u8]) -> Result<(), Error> {
 /// let buf: &mut [u8] = getrandom::fill_uninit(&mut buf)?;
 /// # Ok(()) }
 /// ```
+#[must_use = "random fill may have failed; the buffer contents are unspecified on error"]
 #[inline]
 pub fn fill_uninit(dest: &mut [MaybeUninit<u8>]) -> Result<&mut [u8], Error> {
+    #[cfg(getrandom_warn_early)]
+    boot_entropy::check_once();
+    let len = dest.len();
+    let res = max_fill_len::check(len).and_then(|()| fill_uninit_unobserved(dest));
+    #[cfg(feature = "observer")]
+    observer::notify(len, &res);
+    res
+}
+
+/// Same as [`fill_uninit`], but does not notify the observer.
+///
+/// Used by the default implementations of `u32` and `u64`,
+/// which notify the observer themselves.
+#[inline]
+pub(crate) fn fill_uninit_unobserved(dest: &mut [MaybeUninit<u8>]) -> Result<&mut [u8], Error> {
     if !dest.is_empty() {
         backends::fill_inner(dest)?;
     }
@@ -113,6 +336,90 @@ pub fn fill_uninit(dest: &mut [MaybeUninit<u8>]) -> Result<&mut [u8], Error> {
     })
 }
 
+/// Allocate a `Vec` of `len` random bytes.
+///
+/// This function is equivalent to `let mut v = vec![0u8; len]; fill(&mut v)`,
+/// but fills the spare capacity of the allocated vector using [`fill_uninit`],
+/// so the bytes are not zero-initialized before being overwritten.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), getrandom::Error> {
+/// let nonce = getrandom::fill_vec(24)?;
+/// assert_eq!(nonce.len(), 24);
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "std")]
+#[inline]
+pub fn fill_vec(len: usize) -> Result<std::vec::Vec<u8>, Error> {
+    let mut vec = std::vec::Vec::new();
+    fill_append(&mut vec, len)?;
+    Ok(vec)
+}
+
+/// Append `additional` random bytes to `vec`.
+///
+/// The existing contents of `vec` are left intact. The new bytes are written
+/// into its spare capacity using [`fill_uninit`], so they are not
+/// zero-initialized before being overwritten. If an error is returned, the
+/// length of `vec` is unchanged (though its capacity may have grown).
+///
+/// # Panics
+///
+/// Panics if the new capacity exceeds `isize::MAX` bytes, like [`Vec::reserve`].
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), getrandom::Error> {
+/// let mut data = b"password".to_vec();
+/// getrandom::fill_append(&mut data, 16)?;
+/// assert_eq!(&data[..8], b"password");
+/// assert_eq!(data.len(), 24);
+/// # Ok(()) }
+/// ```
+///
+/// [`Vec::reserve`]: std::vec::Vec::reserve
+#[cfg(feature = "std")]
+#[must_use = "random fill may have failed; the buffer contents are unspecified on error"]
+#[inline]
+pub fn fill_append(vec: &mut std::vec::Vec<u8>, additional: usize) -> Result<(), Error> {
+    vec.reserve(additional);
+    fill_uninit(&mut vec.spare_capacity_mut()[..additional])?;
+    // SAFETY: `reserve` ensured that `vec.len() + additional` does not exceed
+    // the capacity, and `fill_uninit` has initialized the first `additional`
+    // bytes of the spare capacity since it returned `Ok`.
+    unsafe { vec.set_len(vec.len() + additional) };
+    Ok(())
+}
+
+/// Fill each of `bufs` with random bytes.
+///
+/// This is a convenience for code which already holds its buffers as a list
+/// of [`IoSliceMut`](std::io::IoSliceMut)s. Each non-empty buffer is filled
+/// with a separate call to [`fill`]. Either all buffers are completely filled
+/// or an error is returned, in which case the contents of the buffers are
+/// unspecified.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), getrandom::Error> {
+/// use std::io::IoSliceMut;
+///
+/// let mut key = [0u8; 32];
+/// let mut nonce = [0u8; 12];
+/// getrandom::fill_vectored(&mut [IoSliceMut::new(&mut key), IoSliceMut::new(&mut nonce)])?;
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "std")]
+#[must_use = "random fill may have failed; the buffer contents are unspecified on error"]
+#[inline]
+pub fn fill_vectored(bufs: &mut [std::io::IoSliceMut<'_>]) -> Result<(), Error> {
+    bufs.iter_mut().try_for_each(|buf| fill(buf))
+}
+
 /// Get random `u32` from the system's preferred random number source.
 ///
 /// # Examples
@@ -122,9 +429,13 @@ pub fn fill_uninit(dest: &mut [MaybeUninit<u8>]) -> Result<&mut [u8], Error> {
 /// let rng_seed = getrandom::u32()?;
 /// # Ok(()) }
 /// ```
+#[must_use = "random value generation may have failed; the error must be handled"]
 #[inline]
 pub fn u32() -> Result<u32, Error> {
-    backends::inner_u32()
+    let res = backends::inner_u32();
+    #[cfg(feature = "observer")]
+    observer::notify(core::mem::size_of::<u32>(), &res);
+    res
 }
 
 /// Get random `u64` from the system's preferred random number source.
@@ -136,7 +447,195 @@ pub fn u32() -> Result<u32, Error> {
 /// let rng_seed = getrandom::u64()?;
 /// # Ok(()) }
 /// ```
+#[must_use = "random value generation may have failed; the error must be handled"]
 #[inline]
 pub fn u64() -> Result<u64, Error> {
-    backends::inner_u64()
+    let res = backends::inner_u64();
+    #[cfg(feature = "observer")]
+    observer::notify(core::mem::size_of::<u64>(), &res);
+    res
+}
+
+/// Get random `u32` assembled from 4 random bytes in little-endian order.
+///
+/// Unlike [`u32()`], which returns a native-endian value, the result depends
+/// only on the byte stream returned by the backend and not on the target's
+/// endianness. This is useful with deterministic backends (e.g. to produce
+/// the same values on little- and big-endian targets from captured bytes).
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), getrandom::Error> {
+/// let rng_seed = getrandom::u32_le()?;
+/// # Ok(()) }
+/// ```
+#[must_use = "random value generation may have failed; the error must be handled"]
+#[inline]
+pub fn u32_le() -> Result<u32, Error> {
+    let mut buf = [0u8; 4];
+    fill(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+/// Get random `u32` assembled from 4 random bytes in big-endian order.
+///
+/// This is the big-endian version of [`u32_le`], see its documentation for details.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), getrandom::Error> {
+/// let rng_seed = getrandom::u32_be()?;
+/// # Ok(()) }
+/// ```
+#[must_use = "random value generation may have failed; the error must be handled"]
+#[inline]
+pub fn u32_be() -> Result<u32, Error> {
+    let mut buf = [0u8; 4];
+    fill(&mut buf)?;
+    Ok(u32::from_be_bytes(buf))
+}
+
+/// Get random `u64` assembled from 8 random bytes in little-endian order.
+///
+/// This is the `u64` version of [`u32_le`], see its documentation for details.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), getrandom::Error> {
+/// let rng_seed = getrandom::u64_le()?;
+/// # Ok(()) }
+/// ```
+#[must_use = "random value generation may have failed; the error must be handled"]
+#[inline]
+pub fn u64_le() -> Result<u64, Error> {
+    let mut buf = [0u8; 8];
+    fill(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// Get random `u64` assembled from 8 random bytes in big-endian order.
+///
+/// This is the `u64` version of [`u32_be`], see its documentation for details.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), getrandom::Error> {
+/// let rng_seed = getrandom::u64_be()?;
+/// # Ok(()) }
+/// ```
+#[must_use = "random value generation may have failed; the error must be handled"]
+#[inline]
+pub fn u64_be() -> Result<u64, Error> {
+    let mut buf = [0u8; 8];
+    fill(&mut buf)?;
+    Ok(u64::from_be_bytes(buf))
+}
+
+/// Get random `u32` for use as a secret, scrubbing the intermediate buffer.
+///
+/// This function behaves like [`u32()`], but always retrieves the value through
+/// [`fill_uninit`] into a local buffer, which is overwritten with zeros using
+/// volatile writes before returning (on both success and failure). This way
+/// no copy of the value remains in the stack frame of this function, which
+/// is useful for secret values like key material.
+///
+/// Note that the returned value itself may still be copied by the caller or
+/// spilled to the stack by the compiler, so this is a defense-in-depth measure
+/// and not a guarantee that no other copies of the value exist.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), getrandom::Error> {
+/// let key_seed = getrandom::secure_u32()?;
+/// # Ok(()) }
+/// ```
+#[must_use = "random value generation may have failed; the error must be handled"]
+#[inline]
+pub fn secure_u32() -> Result<u32, Error> {
+    let mut buf = [MaybeUninit::<u8>::uninit(); 4];
+    // SAFETY: `bytes` has the same size as `u32`, so the unaligned read is in bounds.
+    let res = fill_uninit(&mut buf)
+        .map(|bytes| unsafe { core::ptr::read_unaligned(bytes.as_ptr().cast::<u32>()) });
+    util::volatile_zero_uninit(&mut buf);
+    res
+}
+
+/// Get random `u64` for use as a secret, scrubbing the intermediate buffer.
+///
+/// This is the `u64` version of [`secure_u32`], see its documentation for details.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), getrandom::Error> {
+/// let key_seed = getrandom::secure_u64()?;
+/// # Ok(()) }
+/// ```
+#[must_use = "random value generation may have failed; the error must be handled"]
+#[inline]
+pub fn secure_u64() -> Result<u64, Error> {
+    let mut buf = [MaybeUninit::<u8>::uninit(); 8];
+    // SAFETY: `bytes` has the same size as `u64`, so the unaligned read is in bounds.
+    let res = fill_uninit(&mut buf)
+        .map(|bytes| unsafe { core::ptr::read_unaligned(bytes.as_ptr().cast::<u64>()) });
+    util::volatile_zero_uninit(&mut buf);
+    res
+}
+
+/// Eagerly initialize the system's preferred random number source.
+///
+/// Some backends initialize lazily on the first request, e.g. by opening
+/// `/dev/urandom`, resolving `getrandom` at runtime or locating the UEFI RNG
+/// protocol. This function performs that initialization without requesting
+/// any random data, so that its cost is paid and its errors are reported at
+/// a predictable point (e.g. at startup). It returns the same error which the
+/// initialization step of the first request would return.
+///
+/// For backends without lazy initialization this is a no-op. Use [`probe`]
+/// to check that the source actually returns random data. Like [`fill`], it
+/// may block during early boot.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), getrandom::Error> {
+/// getrandom::init()?;
+/// # Ok(()) }
+/// ```
+#[inline]
+pub fn init() -> Result<(), Error> {
+    backends::init_inner()
+}
+
+/// Check that the system's preferred random number source is available.
+///
+/// Unlike calling [`fill`] with an empty buffer, which returns immediately
+/// without calling into the backend, this function calls [`init`] and then
+/// requests a single random byte and discards it. This exercises the full
+/// initialization path of the backend (e.g. opening `/dev/urandom` or
+/// resolving `getrandom` at runtime) and returns any error encountered along
+/// the way.
+///
+/// It is intended as a liveness/readiness check (e.g. at service startup or
+/// in a health endpoint), so that an unavailable RNG (for example, blocked by
+/// a seccomp filter) is detected early instead of at the first key generation.
+/// Like [`fill`], it may block during early boot.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), getrandom::Error> {
+/// getrandom::probe()?;
+/// # Ok(()) }
+/// ```
+#[inline]
+pub fn probe() -> Result<(), Error> {
+    init()?;
+    let mut buf = [MaybeUninit::uninit(); 1];
+    backends::fill_inner(&mut buf)
 }
@@ -0,0 +1,68 @@
+//! Global limit on the length of a single request
+use crate::Error;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Maximum number of bytes in a single request, `usize::MAX` if unlimited.
+static MAX_FILL_LEN: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// Limit the number of bytes which can be requested with a single call.
+///
+/// After this function is called, [`fill`](crate::fill) and
+/// [`fill_uninit`](crate::fill_uninit) (and the functions built on top of
+/// them) fail with [`Error::REQUEST_TOO_LARGE`] for buffers longer than
+/// `limit`, without requesting any data from the system. This is a
+/// defense-in-depth measure for code which fills buffers of a length chosen
+/// by untrusted input; such lengths should still be validated by the caller.
+///
+/// The limit is global and can be changed at any time. Passing `usize::MAX`
+/// (the default) removes the limit. Checking the limit costs a single atomic
+/// load per request.
+///
+/// # Examples
+///
+/// ```
+/// use getrandom::Error;
+///
+/// getrandom::set_max_fill_len(1024);
+/// let mut buf = vec![0u8; 4096];
+/// assert_eq!(getrandom::fill(&mut buf), Err(Error::REQUEST_TOO_LARGE));
+/// # getrandom::set_max_fill_len(usize::MAX);
+/// ```
+pub fn set_max_fill_len(limit: usize) {
+    MAX_FILL_LEN.store(limit, Ordering::Relaxed);
+}
+
+/// Check that a request of `len` bytes does not exceed the limit.
+#[inline]
+pub(crate) fn check(len: usize) -> Result<(), Error> {
+    if len > MAX_FILL_LEN.load(Ordering::Relaxed) {
+        Err(Error::REQUEST_TOO_LARGE)
+    } else {
+        Ok(())
+    }
+}
+
+impl Error {
+    /// The request exceeds the limit set with [`set_max_fill_len`].
+    pub const REQUEST_TOO_LARGE: Error = Self::new_internal(5);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, set_max_fill_len, Error};
+
+    // Requests are rejected end-to-end in `tests/mod.rs`. The limit used here
+    // can not be reached by a real request, so the lib tests running
+    // concurrently are not affected.
+    #[test]
+    fn test_max_fill_len() {
+        const LIMIT: usize = usize::MAX - 1;
+
+        set_max_fill_len(LIMIT);
+        assert_eq!(check(LIMIT), Ok(()));
+        assert_eq!(check(LIMIT + 1), Err(Error::REQUEST_TOO_LARGE));
+
+        set_max_fill_len(usize::MAX);
+        assert_eq!(check(usize::MAX), Ok(()));
+    }
+}
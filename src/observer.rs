@@ -0,0 +1,76 @@
+//! Global observer hook, available with the `observer` feature
+use crate::Error;
+use core::{
+    mem, ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+/// Function called after every request for random data.
+///
+/// The first argument is the number of requested bytes, the second argument
+/// is `Ok(())` if the request succeeded and `Err(())` otherwise.
+pub type Observer = fn(usize, Result<(), ()>);
+
+/// Currently installed observer, or null if no observer is installed.
+static OBSERVER: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// Install a global observer which is called after every request for random data.
+///
+/// The observer is called after each call to [`fill`](crate::fill),
+/// [`fill_uninit`](crate::fill_uninit) (and the functions built on top of them),
+/// [`u32`](crate::u32) and [`u64`](crate::u64), with the number of requested
+/// bytes and whether the request succeeded. It is intended for instrumentation,
+/// e.g. audit logging or detecting unexpectedly frequent entropy draws.
+///
+/// Only one observer can be installed at a time: installing a new observer
+/// replaces the previous one. Since the observer may be called concurrently
+/// from multiple threads, it may still observe requests for a short time after
+/// it was replaced.
+///
+/// The observer must be fast, since it runs on every request, and it must not
+/// request random data from this crate itself (directly or indirectly), since
+/// it would be called recursively.
+///
+/// # Examples
+///
+/// ```
+/// use core::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static REQUESTED: AtomicUsize = AtomicUsize::new(0);
+///
+/// fn count(len: usize, _: Result<(), ()>) {
+///     REQUESTED.fetch_add(len, Ordering::Relaxed);
+/// }
+///
+/// # fn main() -> Result<(), getrandom::Error> {
+/// getrandom::set_observer(count);
+/// getrandom::fill(&mut [0u8; 32])?;
+/// assert!(REQUESTED.load(Ordering::Relaxed) >= 32);
+/// getrandom::clear_observer();
+/// # Ok(()) }
+/// ```
+pub fn set_observer(observer: Observer) {
+    OBSERVER.store(observer as *mut (), Ordering::Relaxed);
+}
+
+/// Remove the global observer installed with [`set_observer`], if any.
+pub fn clear_observer() {
+    OBSERVER.store(ptr::null_mut(), Ordering::Relaxed);
+}
+
+/// Notify the installed observer (if any) about a request for `len` bytes.
+#[inline]
+pub(crate) fn notify<T>(len: usize, res: &Result<T, Error>) {
+    let ptr = OBSERVER.load(Ordering::Relaxed);
+    if !ptr.is_null() {
+        notify_slow(ptr, len, res.is_ok());
+    }
+}
+
+#[cold]
+fn notify_slow(ptr: *mut (), len: usize, ok: bool) {
+    // SAFETY: non-null values of `OBSERVER` are only stored by `set_observer`
+    // and were converted from a valid `Observer` function pointer.
+    let observer = unsafe { mem::transmute::<*mut (), Observer>(ptr) };
+    observer(len, if ok { Ok(()) } else { Err(()) });
+}
@@ -0,0 +1,133 @@
+//! Buffer of random bytes refilled under the caller's control
+use crate::{util::volatile_zero, Error};
+use core::{fmt, mem::size_of};
+use std::{boxed::Box, vec};
+
+/// A fixed-capacity buffer of random bytes from the system's random number source.
+///
+/// A `Reservoir` is filled with a single call to [`fill`](crate::fill) when it is
+/// created and on every call to [`refill`](Reservoir::refill). All other methods
+/// serve requests from the buffer without calling the system, which gives the
+/// caller full control over when the (potentially slow) system call happens.
+/// Once the buffer does not hold enough bytes for a request, the request fails
+/// with [`Error::RESERVOIR_EMPTY`] and the caller decides when to refill.
+///
+/// The bytes come directly from the system and are not stretched with a
+/// user-space CSPRNG, so every byte is returned at most once. Bytes are
+/// overwritten with zeros as soon as they are returned, and the whole buffer
+/// is zeroed when the `Reservoir` is dropped.
+///
+/// Only available with the `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), getrandom::Error> {
+/// use getrandom::{Error, Reservoir};
+///
+/// let mut reservoir = Reservoir::new(64)?;
+/// let mut key = [0u8; 32];
+/// reservoir.fill(&mut key)?;
+/// let nonce = reservoir.u64()?;
+/// # let _ = nonce;
+/// assert_eq!(reservoir.remaining(), 24);
+/// assert_eq!(reservoir.fill(&mut key), Err(Error::RESERVOIR_EMPTY));
+///
+/// reservoir.refill()?;
+/// reservoir.fill(&mut key)?;
+/// # Ok(()) }
+/// ```
+pub struct Reservoir {
+    buf: Box<[u8]>,
+    /// Position of the first unused byte in `buf`
+    pos: usize,
+}
+
+impl Reservoir {
+    /// Create a reservoir holding `capacity` random bytes.
+    pub fn new(capacity: usize) -> Result<Self, Error> {
+        let mut reservoir = Reservoir {
+            buf: vec![0; capacity].into_boxed_slice(),
+            pos: capacity,
+        };
+        reservoir.refill()?;
+        Ok(reservoir)
+    }
+
+    /// Replace the contents of the reservoir with new random bytes.
+    ///
+    /// This is the only method which requests random data from the system.
+    /// If it fails, the reservoir is left empty.
+    pub fn refill(&mut self) -> Result<(), Error> {
+        self.pos = self.buf.len();
+        crate::fill(&mut self.buf)?;
+        self.pos = 0;
+        Ok(())
+    }
+
+    /// Number of bytes the reservoir holds when full.
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Number of random bytes which can be returned before a refill is needed.
+    pub fn remaining(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    /// Fill `dest` with random bytes from the reservoir.
+    ///
+    /// Fails with [`Error::RESERVOIR_EMPTY`] without consuming any bytes
+    /// if fewer than `dest.len()` bytes remain.
+    pub fn fill(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        if dest.len() > self.remaining() {
+            return Err(Error::RESERVOIR_EMPTY);
+        }
+        let end = self.pos + dest.len();
+        let src = &mut self.buf[self.pos..end];
+        dest.copy_from_slice(src);
+        volatile_zero(src);
+        self.pos = end;
+        Ok(())
+    }
+
+    /// Get a random `u32` from the reservoir.
+    ///
+    /// Fails with [`Error::RESERVOIR_EMPTY`] if fewer than 4 bytes remain.
+    pub fn u32(&mut self) -> Result<u32, Error> {
+        let mut buf = [0u8; size_of::<u32>()];
+        self.fill(&mut buf)?;
+        Ok(u32::from_ne_bytes(buf))
+    }
+
+    /// Get a random `u64` from the reservoir.
+    ///
+    /// Fails with [`Error::RESERVOIR_EMPTY`] if fewer than 8 bytes remain.
+    pub fn u64(&mut self) -> Result<u64, Error> {
+        let mut buf = [0u8; size_of::<u64>()];
+        self.fill(&mut buf)?;
+        Ok(u64::from_ne_bytes(buf))
+    }
+}
+
+impl Drop for Reservoir {
+    fn drop(&mut self) {
+        volatile_zero(&mut self.buf);
+    }
+}
+
+impl fmt::Debug for Reservoir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Reservoir")
+            .field("capacity", &self.capacity())
+            .field("remaining", &self.remaining())
+            .finish()
+    }
+}
+
+impl Error {
+    /// A [`Reservoir`] does not hold enough bytes for the request.
+    ///
+    /// Only available with the `std` feature.
+    pub const RESERVOIR_EMPTY: Error = Self::new_internal(4);
+}
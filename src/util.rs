@@ -1,6 +1,10 @@
 #![allow(dead_code)]
 use crate::Error;
-use core::{mem::MaybeUninit, ptr, slice};
+use core::{
+    mem::MaybeUninit,
+    ptr, slice,
+    sync::atomic::{compiler_fence, AtomicU8, Ordering},
+};
 
 /// Polyfill for `maybe_uninit_slice` feature's
 /// `MaybeUninit::slice_assume_init_mut`. Every element of `slice` must have
@@ -19,6 +23,22 @@ pub fn uninit_slice_fill_zero(slice: &mut [MaybeUninit<u8>]) -> &mut [u8] {
     unsafe { slice_assume_init_mut(slice) }
 }
 
+/// Overwrite `buf` with zeros using volatile writes, which the compiler
+/// is not allowed to elide even if `buf` is not read afterwards.
+pub fn volatile_zero(buf: &mut [u8]) {
+    // SAFETY: `volatile_zero_uninit` only writes initialized values.
+    volatile_zero_uninit(unsafe { slice_as_uninit_mut(buf) });
+}
+
+/// Same as [`volatile_zero`], but for potentially uninitialized `buf`.
+pub fn volatile_zero_uninit(buf: &mut [MaybeUninit<u8>]) {
+    for byte in buf.iter_mut() {
+        // SAFETY: `byte` is a valid and aligned pointer to `MaybeUninit<u8>`.
+        unsafe { ptr::write_volatile(byte, MaybeUninit::new(0)) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
 #[inline(always)]
 pub fn slice_as_uninit<T>(slice: &[T]) -> &[MaybeUninit<T>] {
     let ptr = ptr_from_ref::<[T]>(slice) as *const [MaybeUninit<T>];
@@ -48,6 +68,20 @@ fn ptr_from_ref<T: ?Sized>(r: &T) -> *const T {
     r
 }
 
+/// Default value of `MAX_FILL_CHUNK` for backends without a per-call limit
+pub const MAX_FILL_CHUNK: usize = usize::MAX;
+
+/// Default implementation of `init_inner` for backends without lazy initialization
+pub fn init_inner() -> Result<(), Error> {
+    Ok(())
+}
+
+/// Default implementation of `fill_nonblocking_inner` for backends which can
+/// not avoid blocking (or never block)
+pub fn fill_nonblocking_inner(dest: &mut [MaybeUninit<u8>]) -> Result<(), Error> {
+    crate::backends::fill_inner(dest)
+}
+
 /// Default implementation of `inner_u32` on top of `fill_uninit`
 pub fn inner_u32() -> Result<u32, Error> {
     let mut res = MaybeUninit::<u32>::uninit();
@@ -56,7 +90,7 @@ pub fn inner_u32() -> Result<u32, Error> {
         let p: *mut MaybeUninit<u8> = res.as_mut_ptr().cast();
         slice::from_raw_parts_mut(p, core::mem::size_of::<u32>())
     };
-    crate::fill_uninit(dst)?;
+    crate::fill_uninit_unobserved(dst)?;
     // SAFETY: `dst` has been fully initialized by `imp::fill_inner`
     // since it returned `Ok`.
     Ok(unsafe { res.assume_init() })
@@ -70,13 +104,161 @@ pub fn inner_u64() -> Result<u64, Error> {
         let p: *mut MaybeUninit<u8> = res.as_mut_ptr().cast();
         slice::from_raw_parts_mut(p, core::mem::size_of::<u64>())
     };
-    crate::fill_uninit(dst)?;
+    crate::fill_uninit_unobserved(dst)?;
     // SAFETY: `dst` has been fully initialized by `imp::fill_inner`
     // since it returned `Ok`.
     Ok(unsafe { res.assume_init() })
 }
 
+/// Fill `dest` using a source which returns random `u64` values.
+///
+/// Aligned 8-byte chunks are written directly, while the unaligned
+/// prefix and suffix (if any) take one additional value each.
+#[inline]
+pub fn fill_with_u64(dest: &mut [MaybeUninit<u8>], mut next_u64: impl FnMut() -> u64) {
+    // SAFETY: `MaybeUninit<u64>` does not have any validity requirements
+    let (prefix, chunks, suffix) = unsafe { dest.align_to_mut::<MaybeUninit<u64>>() };
+
+    if !prefix.is_empty() {
+        let val = next_u64();
+        let src = (&val as *const u64).cast();
+        // SAFETY: `prefix` is shorter than 8 bytes
+        unsafe { ptr::copy_nonoverlapping(src, prefix.as_mut_ptr(), prefix.len()) };
+    }
+
+    for dst in chunks {
+        dst.write(next_u64());
+    }
+
+    if !suffix.is_empty() {
+        let val = next_u64();
+        let src = (&val as *const u64).cast();
+        // SAFETY: `suffix` is shorter than 8 bytes
+        unsafe { ptr::copy_nonoverlapping(src, suffix.as_mut_ptr(), suffix.len()) };
+    }
+}
+
+/// Fill `dest` with an incrementing (wrapping) byte counter, i.e.
+/// `dest[i] = start + i`, where `start` is the value of `counter`, which is
+/// advanced past the written values.
+///
+/// Used by the backends for tests, it is NOT random!
+pub fn fill_counter(counter: &AtomicU8, dest: &mut [MaybeUninit<u8>]) {
+    // The counter wraps around, so only the length modulo 256 matters
+    #[allow(clippy::cast_possible_truncation)]
+    let len = dest.len() as u8;
+    let mut val = counter.fetch_add(len, Ordering::Relaxed);
+    for b in dest {
+        b.write(val);
+        val = val.wrapping_add(1);
+    }
+}
+
+/// Skip the first `len` bytes of `buf`, which a system call reported as written.
+///
+/// A `len` larger than `buf` indicates a bug in the system call or its wrapper
+/// and is reported as [`Error::UNEXPECTED`]. With `getrandom_debug_assert_fills`
+/// debug builds panic instead.
+#[inline]
+pub fn skip_filled(
+    buf: &mut [MaybeUninit<u8>],
+    len: usize,
+) -> Result<&mut [MaybeUninit<u8>], Error> {
+    #[cfg(getrandom_debug_assert_fills)]
+    debug_assert!(
+        len <= buf.len(),
+        "{} bytes reported as written into a buffer of {}",
+        len,
+        buf.len()
+    );
+    buf.get_mut(len..).ok_or(Error::UNEXPECTED)
+}
+
+/// Parse a decimal `u64` at compile time (e.g. from `option_env!`),
+/// panicking with `err` if `s` is not a valid decimal `u64`.
+#[allow(clippy::cast_lossless)] // TODO: `u64::from` can't be used in `const fn`
+pub(crate) const fn parse_decimal(s: &str, err: &str) -> u64 {
+    let s = s.as_bytes();
+    assert!(!s.is_empty(), "{}", err);
+    let mut res: u64 = 0;
+    let mut i = 0;
+    while i < s.len() {
+        assert!(s[i].is_ascii_digit(), "{}", err);
+        res = match res.checked_mul(10) {
+            Some(v) => match v.checked_add((s[i] - b'0') as u64) {
+                Some(v) => v,
+                None => panic!("{}", err),
+            },
+            None => panic!("{}", err),
+        };
+        i += 1;
+    }
+    res
+}
+
 /// Truncates `u64` and returns the lower 32 bits as `u32`
 pub(crate) fn truncate(val: u64) -> u32 {
     u32::try_from(val & u64::from(u32::MAX)).expect("The higher 32 bits are masked")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        fill_with_u64, skip_filled, slice_assume_init_mut, volatile_zero_uninit, MaybeUninit,
+    };
+
+    #[test]
+    fn test_fill_with_u64() {
+        let mut buf = [MaybeUninit::uninit(); 40];
+        // Cover all combinations of unaligned prefix and suffix lengths
+        for start in 0..8 {
+            for len in 0..32 {
+                let mut n = 0u64;
+                let dest = &mut buf[start..][..len];
+                fill_with_u64(dest, || {
+                    n += 1;
+                    0x0101_0101_0101_0101 * n
+                });
+                // SAFETY: `fill_with_u64` initializes the whole slice
+                let dest = unsafe { slice_assume_init_mut(dest) };
+                assert!(dest.iter().all(|&b| b != 0));
+                assert!(n <= 6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_volatile_zero_uninit() {
+        let mut buf = [MaybeUninit::new(0xa5u8); 13];
+        volatile_zero_uninit(&mut buf[..12]);
+        // SAFETY: `buf` was initialized above and only overwritten with zeros
+        let buf = unsafe { slice_assume_init_mut(&mut buf) };
+        assert_eq!(buf[..12], [0; 12]);
+        assert_eq!(buf[12], 0xa5);
+    }
+
+    #[test]
+    fn test_skip_filled() {
+        let mut buf = [MaybeUninit::new(0u8); 8];
+        assert_eq!(skip_filled(&mut buf, 3).unwrap().len(), 5);
+        assert_eq!(skip_filled(&mut buf, 8).unwrap().len(), 0);
+    }
+
+    #[cfg(not(all(getrandom_debug_assert_fills, debug_assertions)))]
+    #[test]
+    fn test_skip_filled_too_long() {
+        let mut buf = [MaybeUninit::new(0u8); 8];
+        assert_eq!(
+            skip_filled(&mut buf, 9).err(),
+            Some(crate::Error::UNEXPECTED)
+        );
+    }
+
+    #[cfg(all(getrandom_debug_assert_fills, debug_assertions))]
+    #[test]
+    #[should_panic = "9 bytes reported as written into a buffer of 8"]
+    fn test_skip_filled_too_long() {
+        let mut buf = [MaybeUninit::new(0u8); 8];
+        let _ = skip_filled(&mut buf, 9);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::Error;
+use crate::{util::skip_filled, Error};
 use core::mem::MaybeUninit;
 
 cfg_if! {
@@ -8,7 +8,14 @@ cfg_if! {
         use libc::__errno_location as errno_location;
     } else if #[cfg(any(target_os = "solaris", target_os = "illumos"))] {
         use libc::___errno as errno_location;
-    } else if #[cfg(any(target_os = "macos", target_os = "freebsd"))] {
+    } else if #[cfg(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "visionos",
+        target_os = "watchos",
+        target_os = "tvos",
+        target_os = "freebsd",
+    ))] {
         use libc::__error as errno_location;
     } else if #[cfg(target_os = "haiku")] {
         use libc::_errnop as errno_location;
@@ -60,7 +67,7 @@ pub(crate) fn sys_fill_exact(
         match res {
             res if res > 0 => {
                 let len = usize::try_from(res).map_err(|_| Error::UNEXPECTED)?;
-                buf = buf.get_mut(len..).ok_or(Error::UNEXPECTED)?;
+                buf = skip_filled(buf, len)?;
             }
             -1 => {
                 let err = last_os_error();
@@ -77,3 +84,62 @@ pub(crate) fn sys_fill_exact(
     }
     Ok(())
 }
+
+/// Report `EAGAIN` returned by a non-blocking call as [`Error::WOULD_BLOCK`].
+#[allow(dead_code)]
+pub(crate) fn map_eagain(err: Error) -> Error {
+    if err.raw_os_error() == Some(libc::EAGAIN) {
+        Error::WOULD_BLOCK
+    } else {
+        err
+    }
+}
+
+#[cfg(all(test, any(target_os = "linux", target_os = "dragonfly")))]
+mod tests {
+    use super::{errno_location, map_eagain, sys_fill_exact};
+    use crate::Error;
+    use core::{cell::Cell, mem::MaybeUninit};
+
+    // Check that after a short write or `EINTR`, `sys_fill` is only
+    // given the unfilled tail of the buffer.
+    #[test]
+    fn test_sys_fill_exact_tail() {
+        const LEN: usize = 100;
+        // (offset of the tail passed to `sys_fill`, return value)
+        const STEPS: [(usize, isize); 4] = [(0, 30), (30, -1), (30, 25), (55, 45)];
+
+        let mut buf = [MaybeUninit::new(0u8); LEN];
+        let base = buf.as_ptr();
+        let step = Cell::new(0);
+        sys_fill_exact(&mut buf, |tail| {
+            let (offset, ret) = STEPS[step.get()];
+            step.set(step.get() + 1);
+            assert_eq!(tail.as_ptr(), base.wrapping_add(offset));
+            assert_eq!(tail.len(), LEN - offset);
+            match usize::try_from(ret) {
+                Ok(n) => tail[..n].fill(MaybeUninit::new(u8::try_from(step.get()).unwrap())),
+                Err(_) => unsafe { *errno_location() = libc::EINTR },
+            }
+            ret
+        })
+        .unwrap();
+        assert_eq!(step.get(), STEPS.len());
+
+        let buf = buf.map(|b| unsafe { b.assume_init() });
+        assert!(buf[..30].iter().all(|&b| b == 1));
+        assert!(buf[30..55].iter().all(|&b| b == 3));
+        assert!(buf[55..].iter().all(|&b| b == 4));
+    }
+
+    #[test]
+    fn test_map_eagain() {
+        let mut buf = [MaybeUninit::new(0u8); 4];
+        let res = sys_fill_exact(&mut buf, |_| {
+            unsafe { *errno_location() = libc::EAGAIN };
+            -1
+        });
+        assert_eq!(res.map_err(map_eagain), Err(Error::WOULD_BLOCK));
+        assert_eq!(map_eagain(Error::UNSUPPORTED), Error::UNSUPPORTED);
+    }
+}
@@ -0,0 +1,38 @@
+//! Safe wrapper around the Zircon `zx_cprng_draw` system call
+use core::mem::MaybeUninit;
+
+#[link(name = "zircon")]
+extern "C" {
+    #[link_name = "zx_cprng_draw"]
+    fn zx_cprng_draw_raw(buffer: *mut u8, length: usize);
+}
+
+/// Maximum number of bytes requested with a single `zx_cprng_draw` call.
+///
+/// Older Zircon versions reject longer requests (`ZX_CPRNG_DRAW_MAX_LEN`).
+pub(crate) const CPRNG_DRAW_MAX_LEN: usize = 256;
+
+/// Fill `dest` with `zx_cprng_draw`, splitting it into chunks of at most
+/// [`CPRNG_DRAW_MAX_LEN`] bytes.
+pub(crate) fn cprng_draw(dest: &mut [MaybeUninit<u8>]) {
+    for chunk in dest.chunks_mut(CPRNG_DRAW_MAX_LEN) {
+        // `zx_cprng_draw` cannot fail, it terminates the process instead.
+        unsafe { zx_cprng_draw_raw(chunk.as_mut_ptr().cast::<u8>(), chunk.len()) }
+    }
+}
+
+/// Fill `buf` with random bytes using the Zircon [`zx_cprng_draw`] system call.
+///
+/// This is the primitive used by the Fuchsia backend, exposed for code which
+/// needs to call it directly (e.g. to fill a mapped VMO). Requests longer
+/// than 256 bytes are split into multiple system calls. The system call
+/// cannot fail: the kernel terminates the process instead.
+///
+/// Available only on Fuchsia, regardless of the selected backend.
+///
+/// [`zx_cprng_draw`]: https://fuchsia.dev/fuchsia-src/reference/syscalls/cprng_draw
+#[inline]
+pub fn zx_cprng_draw(buf: &mut [u8]) {
+    // SAFETY: `cprng_draw` only writes initialized bytes
+    cprng_draw(unsafe { crate::util::slice_as_uninit_mut(buf) })
+}
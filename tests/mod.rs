@@ -1,5 +1,17 @@
+// These tests assume random output, so they are skipped for the backends
+// which do not provide it. Their unit tests are run with `cargo test --lib`.
+#![cfg(not(any(
+    getrandom_backend = "counter",
+    getrandom_backend = "mock",
+    getrandom_backend = "test_failing",
+    getrandom_backend = "proxy",
+    getrandom_backend = "test_slow",
+    getrandom_backend = "panic",
+    getrandom_backend = "verifier"
+)))]
+
 use core::mem::MaybeUninit;
-use getrandom::{fill, fill_uninit};
+use getrandom::{fill, fill_scrub_on_error, fill_uninit};
 
 #[cfg(all(
     getrandom_backend = "wasm_js",
@@ -8,6 +20,19 @@ use getrandom::{fill, fill_uninit};
 ))]
 use wasm_bindgen_test::wasm_bindgen_test as test;
 
+#[test]
+fn test_init() {
+    getrandom::init().unwrap();
+    // Initialization is idempotent and requests still work afterwards
+    getrandom::init().unwrap();
+    fill(&mut [0u8; 4]).unwrap();
+}
+
+#[test]
+fn test_probe() {
+    getrandom::probe().unwrap();
+}
+
 #[test]
 fn test_zero() {
     // Test that APIs are happy with zero-length requests
@@ -114,6 +139,28 @@ fn test_diff_u64() {
     assert!(d1 < 4500);
 }
 
+#[test]
+fn test_diff_endian() {
+    fn check<T: DiffBits>(f: fn() -> Result<T, getrandom::Error>) {
+        let n = 1000 / core::mem::size_of::<T>();
+        let v1: Vec<T> = (0..n).map(|_| f().unwrap()).collect();
+        let v2: Vec<T> = (0..n).map(|_| f().unwrap()).collect();
+
+        // Between 3.5 and 4.5 bits per byte should differ. Probability of failure:
+        // ~ 2^(-94) = 2 * CDF[BinomialDistribution[8000, 0.5], 3500]
+        let d1 = num_diff_bits(&v1, &v2);
+        assert!(d1 > 3500);
+        assert!(d1 < 4500);
+    }
+
+    for f in [getrandom::u32_le, getrandom::u32_be] {
+        check(f);
+    }
+    for f in [getrandom::u64_le, getrandom::u64_be] {
+        check(f);
+    }
+}
+
 #[test]
 fn test_small() {
     const N: usize = 64;
@@ -171,12 +218,231 @@ fn test_small_uninit() {
     }
 }
 
+// Assumes that the test machine finished booting long ago, so the OS backends
+// are ready
+#[test]
+fn test_poll_fill() {
+    use core::task::Poll;
+
+    let mut buf = [0u8; 32];
+    assert_eq!(getrandom::poll_fill(&mut buf), Poll::Ready(Ok(())));
+    // Probability of failure: 2^(-256)
+    assert!(buf.iter().any(|&b| b != 0));
+    assert_eq!(getrandom::poll_fill(&mut []), Poll::Ready(Ok(())));
+}
+
+// Tests that `fill_ints` covers every byte of the slice for several widths
+#[test]
+fn test_fill_ints() {
+    fn check<T: getrandom::IntFill + Default + PartialEq>() {
+        const N: usize = 64;
+        let mut v = [T::default(); N];
+        getrandom::fill_ints(&mut v).unwrap();
+        // Probability of a random value being zero is at most 2^-8, so the
+        // probability of 32 or more zeros among 64 values is negligible.
+        assert!(v.iter().filter(|&x| *x == T::default()).count() < N / 2);
+        // The last value is filled as well. Checked for the wider types only,
+        // so the probability of failure is at most 2^-32.
+        if core::mem::size_of::<T>() >= 4 {
+            assert!(v[N - 1] != T::default());
+        }
+        getrandom::fill_ints::<T>(&mut []).unwrap();
+    }
+    check::<u8>();
+    check::<u16>();
+    check::<u32>();
+    check::<u64>();
+    check::<u128>();
+    check::<usize>();
+    check::<i8>();
+    check::<i16>();
+    check::<i32>();
+    check::<i64>();
+    check::<i128>();
+    check::<isize>();
+}
+
+#[test]
+fn test_diff_fill_ints() {
+    const N: usize = 1000 / 8;
+    let mut v1 = [0u64; N];
+    let mut v2 = [0u64; N];
+    getrandom::fill_ints(&mut v1).unwrap();
+    getrandom::fill_ints(&mut v2).unwrap();
+
+    // Between 3.5 and 4.5 bits per byte should differ. Probability of failure:
+    // ~ 2^(-94) = 2 * CDF[BinomialDistribution[8000, 0.5], 3500]
+    let d1 = num_diff_bits(&v1, &v2);
+    assert!(d1 > 3500);
+    assert!(d1 < 4500);
+}
+
+// Test that filling unaligned subslices does not write outside of them
+#[test]
+fn test_unaligned() {
+    for offset in 1..8 {
+        for len in [1, 7, 8, 9, 31] {
+            let mut buf = [0u8; 48];
+            fill(&mut buf[offset..][..len]).unwrap();
+            assert!(buf[..offset].iter().all(|&b| b == 0));
+            assert!(buf[offset + len..].iter().all(|&b| b == 0));
+        }
+    }
+}
+
+#[test]
+fn test_scrub_on_error() {
+    const N: usize = 1000;
+    let mut v1 = [0u8; N];
+    let mut v2 = [0u8; N];
+    fill_scrub_on_error(&mut v1).unwrap();
+    fill_scrub_on_error(&mut v2).unwrap();
+    let d = num_diff_bits(&v1, &v2);
+    assert!(d > 3500);
+    assert!(d < 4500);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_fill_vec() {
+    for len in [0, 1, 7, 100_000] {
+        assert_eq!(getrandom::fill_vec(len).unwrap().len(), len);
+    }
+
+    let v1 = getrandom::fill_vec(1000).unwrap();
+    let v2 = getrandom::fill_vec(1000).unwrap();
+    // Between 3.5 and 4.5 bits per byte should differ. Probability of failure:
+    // ~ 2^(-94) = 2 * CDF[BinomialDistribution[8000, 0.5], 3500]
+    let d = num_diff_bits(&v1, &v2);
+    assert!(d > 3500);
+    assert!(d < 4500);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_fill_append() {
+    let mut v = b"prefix".to_vec();
+    getrandom::fill_append(&mut v, 0).unwrap();
+    assert_eq!(v, b"prefix");
+
+    getrandom::fill_append(&mut v, 1000).unwrap();
+    assert_eq!(v.len(), 1006);
+    assert_eq!(&v[..6], b"prefix");
+    getrandom::fill_append(&mut v, 1000).unwrap();
+    assert_eq!(v.len(), 2006);
+    assert_eq!(&v[..6], b"prefix");
+
+    // Between 3.5 and 4.5 bits per byte should differ. Probability of failure:
+    // ~ 2^(-94) = 2 * CDF[BinomialDistribution[8000, 0.5], 3500]
+    let d = num_diff_bits(&v[6..1006], &v[1006..]);
+    assert!(d > 3500);
+    assert!(d < 4500);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_fill_vectored() {
+    use std::io::IoSliceMut;
+
+    getrandom::fill_vectored(&mut []).unwrap();
+
+    let mut small = [0u8; 1];
+    let mut empty1 = [0u8; 0];
+    let mut v1 = [0u8; 1000];
+    let mut empty2 = [0u8; 0];
+    let mut v2 = [0u8; 1000];
+    getrandom::fill_vectored(&mut [
+        IoSliceMut::new(&mut small),
+        IoSliceMut::new(&mut empty1),
+        IoSliceMut::new(&mut v1),
+        IoSliceMut::new(&mut empty2),
+        IoSliceMut::new(&mut v2),
+    ])
+    .unwrap();
+
+    // Between 3.5 and 4.5 bits per byte should differ. Probability of failure:
+    // ~ 2^(-94) = 2 * CDF[BinomialDistribution[8000, 0.5], 3500]
+    let d = num_diff_bits(&v1, &v2);
+    assert!(d > 3500);
+    assert!(d < 4500);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_reservoir() {
+    use getrandom::{Error, Reservoir};
+
+    let mut r = Reservoir::new(16).unwrap();
+    assert_eq!(r.capacity(), 16);
+    assert_eq!(r.remaining(), 16);
+
+    let mut buf = [0u8; 4];
+    r.fill(&mut buf).unwrap();
+    r.u32().unwrap();
+    r.u64().unwrap();
+    assert_eq!(r.remaining(), 0);
+    // Empty requests succeed even when drained
+    r.fill(&mut []).unwrap();
+    assert_eq!(r.u32(), Err(Error::RESERVOIR_EMPTY));
+
+    r.refill().unwrap();
+    assert_eq!(r.remaining(), 16);
+    r.u64().unwrap();
+    // Failed requests don't consume any bytes
+    assert_eq!(r.fill(&mut [0u8; 9]), Err(Error::RESERVOIR_EMPTY));
+    assert_eq!(r.remaining(), 8);
+    r.fill(&mut [0u8; 8]).unwrap();
+    assert_eq!(r.remaining(), 0);
+
+    let mut r = Reservoir::new(0).unwrap();
+    assert_eq!(r.u32(), Err(Error::RESERVOIR_EMPTY));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_reservoir_diff() {
+    let mut r = getrandom::Reservoir::new(2000).unwrap();
+    let mut v1 = [0u8; 1000];
+    let mut v2 = [0u8; 1000];
+    r.fill(&mut v1).unwrap();
+    r.fill(&mut v2).unwrap();
+
+    // Between 3.5 and 4.5 bits per byte should differ. Probability of failure:
+    // ~ 2^(-94) = 2 * CDF[BinomialDistribution[8000, 0.5], 3500]
+    let d = num_diff_bits(&v1, &v2);
+    assert!(d > 3500);
+    assert!(d < 4500);
+}
+
+// Assumes that the test machine finished booting long ago
+#[cfg(getrandom_warn_early)]
+#[test]
+fn test_boot_entropy_was_low() {
+    fill(&mut [0u8; 4]).unwrap();
+    assert_eq!(getrandom::boot_entropy_was_low(), Some(false));
+}
+
 #[test]
 fn test_huge() {
     let mut huge = [0u8; 100_000];
     fill(&mut huge).unwrap();
 }
 
+#[test]
+fn test_max_fill_len() {
+    // Larger than any other request made by these tests running concurrently
+    const LIMIT: usize = 1 << 24;
+    let mut buf = vec![0u8; LIMIT + 1];
+
+    getrandom::set_max_fill_len(LIMIT);
+    let res = fill(&mut buf);
+    getrandom::set_max_fill_len(usize::MAX);
+
+    assert_eq!(res, Err(getrandom::Error::REQUEST_TOO_LARGE));
+    // The request was rejected before any data was requested from the system
+    assert!(buf.iter().all(|&b| b == 0));
+}
+
 #[test]
 fn test_huge_uninit() {
     const N: usize = 100_000;
@@ -185,6 +451,60 @@ fn test_huge_uninit() {
     assert_eq!(res.len(), N);
 }
 
+// The `KERN_ARND` fallback returns at most 256 bytes per call,
+// so check that requests around that limit are fully filled.
+#[cfg(any(target_os = "freebsd", target_os = "netbsd"))]
+#[test]
+fn test_kern_arnd_chunks() {
+    for len in [255, 256, 257, 511, 512, 513, 4096] {
+        let mut buf = vec![0u8; len];
+        fill(&mut buf).unwrap();
+        // Probability of failure: 2^(-128) per length
+        assert!(buf[len - 16..].iter().any(|&b| b != 0));
+    }
+}
+
+// getentropy(3) fails for requests longer than 256 bytes, so check that
+// larger requests are split into chunks and fully filled.
+#[cfg(all(target_os = "freebsd", getrandom_freebsd_getentropy))]
+#[test]
+fn test_freebsd_getentropy_huge() {
+    for len in [255, 256, 257, 511, 512, 513, 100_000] {
+        let mut buf = vec![0u8; len];
+        fill(&mut buf).unwrap();
+        // Probability of failure: 2^(-128) per length
+        assert!(buf[len - 16..].iter().any(|&b| b != 0));
+    }
+}
+
+// The exposed `zx_cprng_draw` wrapper splits requests into 256-byte chunks,
+// so check that requests around that limit are fully filled.
+#[cfg(target_os = "fuchsia")]
+#[test]
+fn test_zx_cprng_draw() {
+    for len in [0, 1, 255, 256, 257, 511, 512, 513, 4096] {
+        let mut buf = vec![0u8; len];
+        getrandom::zx_cprng_draw(&mut buf);
+        if len >= 16 {
+            // Probability of failure: 2^(-128) per length
+            assert!(buf[len - 16..].iter().any(|&b| b != 0));
+        }
+    }
+}
+
+// DragonFly's getrandom(2) does not guarantee full reads of large buffers.
+// Check that the tail of buffers around common per-call limits is filled.
+#[cfg(target_os = "dragonfly")]
+#[test]
+fn test_dragonfly_huge() {
+    for len in [255, 256, 257, 4096, 65_535, 65_536, 65_537, 1 << 20] {
+        let mut buf = vec![0u8; len];
+        fill(&mut buf).unwrap();
+        // Probability of failure: 2^(-128) per length
+        assert!(buf[len - 16..].iter().any(|&b| b != 0));
+    }
+}
+
 #[test]
 #[cfg_attr(
     target_arch = "wasm32",
@@ -273,6 +593,12 @@ mod custom {
         if len == 142 {
             return Err(Error::new_custom(142));
         }
+        // Leave the last byte uninitialized, see `test_msan_incomplete`
+        #[cfg(getrandom_msan)]
+        let len = match std::env::var_os(MSAN_CHILD_VAR) {
+            Some(_) => len - 1,
+            None => len,
+        };
 
         let dest_u32 = dest.cast::<u32>();
         let ts = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
@@ -298,4 +624,284 @@ mod custom {
         let res = getrandom::fill(&mut buf);
         assert!(res.is_err());
     }
+
+    #[test]
+    fn test_scrub_on_error() {
+        let mut buf = [0xAAu8; 142];
+        let res = getrandom::fill_scrub_on_error(&mut buf);
+        assert_eq!(res, Err(Error::new_custom(142)));
+        assert!(buf.iter().all(|&b| b == 0));
+    }
+
+    #[cfg(getrandom_msan)]
+    const MSAN_CHILD_VAR: &str = "GETRANDOM_TEST_MSAN_INCOMPLETE_CHILD";
+
+    // Fills a buffer with the incomplete custom implementation, which
+    // must be detected by MemorySanitizer and abort the process
+    #[cfg(getrandom_msan)]
+    #[test]
+    fn msan_incomplete_fill() {
+        if std::env::var_os(MSAN_CHILD_VAR).is_none() {
+            return;
+        }
+        let mut buf = [core::mem::MaybeUninit::uninit(); 16];
+        let _ = getrandom::fill_uninit(&mut buf);
+    }
+
+    #[cfg(getrandom_msan)]
+    #[test]
+    fn test_msan_incomplete() {
+        use std::{env, process::Command};
+
+        let out = Command::new(env::current_exe().unwrap())
+            .args(["--exact", "custom::msan_incomplete_fill"])
+            .env(MSAN_CHILD_VAR, "1")
+            .output()
+            .unwrap();
+        assert!(!out.status.success());
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        assert!(stderr.contains("MemorySanitizer"), "{}", stderr);
+    }
+}
+
+#[cfg(all(getrandom_file_reopen, getrandom_test_linux_fallback))]
+mod file_reopen {
+    use std::{env, fs, process::Command};
+
+    const CHILD_VAR: &str = "GETRANDOM_TEST_FILE_REOPEN_CHILD";
+
+    fn close_urandom_fds() -> usize {
+        let mut closed = 0;
+        let dir = fs::read_dir("/proc/self/fd").unwrap();
+        // Collect first, so that the file descriptor of `dir` is not closed
+        let entries: Vec<_> = dir.map(|entry| entry.unwrap().path()).collect();
+        for path in entries {
+            if fs::read_link(&path).ok().as_deref() == Some("/dev/urandom".as_ref()) {
+                let fd: i32 = path.file_name().unwrap().to_str().unwrap().parse().unwrap();
+                assert_eq!(unsafe { libc::close(fd) }, 0);
+                closed += 1;
+            }
+        }
+        closed
+    }
+
+    // Closes the file descriptor opened by `getrandom` behind its back,
+    // run in a separate process to avoid interfering with other tests
+    #[test]
+    fn reopen_after_close() {
+        if env::var_os(CHILD_VAR).is_none() {
+            return;
+        }
+        let mut buf = [0u8; 32];
+        getrandom::fill(&mut buf).unwrap();
+        assert_eq!(close_urandom_fds(), 1);
+        // The next read fails with `EBADF` and re-opens `/dev/urandom`
+        getrandom::fill(&mut buf).unwrap();
+        assert_eq!(close_urandom_fds(), 1);
+    }
+
+    #[test]
+    fn test_reopen() {
+        let out = Command::new(env::current_exe().unwrap())
+            .args(["--exact", "file_reopen::reopen_after_close"])
+            .env(CHILD_VAR, "1")
+            .output()
+            .unwrap();
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stdout)
+        );
+    }
+}
+
+#[cfg(getrandom_backend = "deterministic")]
+mod deterministic {
+    use std::{env, process::Command};
+
+    const CHILD_VAR: &str = "GETRANDOM_TEST_DETERMINISTIC_CHILD";
+
+    // Prints output of the first calls in a fresh process, used by `test_reproducible`
+    #[test]
+    fn print_output() {
+        if env::var_os(CHILD_VAR).is_none() {
+            return;
+        }
+        let mut buf = [0u8; 37];
+        getrandom::fill(&mut buf).unwrap();
+        let val = getrandom::u64().unwrap();
+        println!("output: {:?} {}", buf, val);
+    }
+
+    fn child_output() -> String {
+        let out = Command::new(env::current_exe().unwrap())
+            .args(["--exact", "deterministic::print_output", "--nocapture"])
+            .env(CHILD_VAR, "1")
+            .output()
+            .unwrap();
+        assert!(out.status.success());
+        let stdout = String::from_utf8(out.stdout).unwrap();
+        // libtest may print the test name on the same line as our output
+        let pos = stdout.find("output: ").unwrap();
+        stdout[pos..].lines().next().unwrap().to_owned()
+    }
+
+    // Test that two processes with the same seed produce identical output
+    #[test]
+    fn test_reproducible() {
+        assert_eq!(child_output(), child_output());
+    }
+}
+
+#[cfg(all(
+    getrandom_backend = "wasm_js",
+    target_arch = "wasm32",
+    target_os = "unknown"
+))]
+mod wasm_js {
+    use getrandom::Error;
+    use wasm_bindgen::prelude::wasm_bindgen;
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    #[wasm_bindgen(inline_js = r#"
+        let saved;
+        export function replace_crypto(throwing) {
+            saved = Object.getOwnPropertyDescriptor(globalThis, "crypto");
+            const value = throwing
+                ? { getRandomValues() { throw new Error("getRandomValues failure"); } }
+                : undefined;
+            Object.defineProperty(globalThis, "crypto", { value, configurable: true });
+        }
+        export function restore_crypto() {
+            if (saved === undefined) {
+                delete globalThis.crypto;
+            } else {
+                Object.defineProperty(globalThis, "crypto", saved);
+            }
+        }
+    "#)]
+    extern "C" {
+        fn replace_crypto(throwing: bool);
+        fn restore_crypto();
+    }
+
+    fn fill_with_replaced_crypto(throwing: bool) -> Result<(), Error> {
+        replace_crypto(throwing);
+        let res = getrandom::fill(&mut [0u8; 16]);
+        restore_crypto();
+        res
+    }
+
+    #[test]
+    fn test_crypto_unavailable() {
+        let res = fill_with_replaced_crypto(false);
+        assert_eq!(res, Err(Error::WEB_CRYPTO_UNAVAILABLE));
+    }
+
+    // `getRandomValues` throws `QuotaExceededError` for requests larger than
+    // 65536 bytes, make sure such buffers are split into multiple calls
+    #[test]
+    fn test_large_fill() {
+        let mut buf = vec![0u8; 3 * 65536 + 1];
+        getrandom::fill(&mut buf).unwrap();
+        assert!(buf[65536 * 3 - 8..].iter().any(|&b| b != 0));
+    }
+
+    #[test]
+    fn test_get_random_values_failed() {
+        let res = fill_with_replaced_crypto(true);
+        assert_eq!(res, Err(Error::WEB_GET_RANDOM_VALUES_FAILED));
+    }
+}
+
+#[cfg(feature = "ffi")]
+mod ffi {
+    extern "C" {
+        fn getrandom_fill(ptr: *mut u8, len: usize) -> u32;
+    }
+
+    #[test]
+    fn test_getrandom_fill() {
+        let mut buf = [0u8; 32];
+        assert_eq!(unsafe { getrandom_fill(buf.as_mut_ptr(), buf.len()) }, 0);
+        assert!(buf.iter().any(|&b| b != 0));
+        assert_eq!(unsafe { getrandom_fill(core::ptr::null_mut(), 0) }, 0);
+    }
+}
+
+#[cfg(feature = "observer")]
+mod observer {
+    use getrandom::{clear_observer, set_observer};
+    use std::{
+        sync::Mutex,
+        thread::{self, ThreadId},
+        vec::Vec,
+    };
+
+    type Observation = (ThreadId, usize, Result<(), ()>);
+
+    static OBSERVED: Mutex<Vec<Observation>> = Mutex::new(Vec::new());
+
+    fn record(len: usize, res: Result<(), ()>) {
+        let id = thread::current().id();
+        OBSERVED.lock().unwrap().push((id, len, res));
+    }
+
+    // Other tests run concurrently, so only requests made by the current thread are checked.
+    fn observed() -> Vec<(usize, Result<(), ()>)> {
+        let id = thread::current().id();
+        let observed = OBSERVED.lock().unwrap();
+        observed
+            .iter()
+            .filter(|(tid, _, _)| *tid == id)
+            .map(|&(_, len, res)| (len, res))
+            .collect()
+    }
+
+    #[test]
+    fn test_observer() {
+        set_observer(record);
+        getrandom::fill(&mut [0u8; 1234]).unwrap();
+        getrandom::fill_uninit(&mut [core::mem::MaybeUninit::uninit(); 4321]).unwrap();
+        getrandom::fill(&mut []).unwrap();
+        getrandom::u32().unwrap();
+        getrandom::u64().unwrap();
+        let expected = [
+            (1234, Ok(())),
+            (4321, Ok(())),
+            (0, Ok(())),
+            (4, Ok(())),
+            (8, Ok(())),
+        ];
+        assert_eq!(observed(), expected);
+
+        clear_observer();
+        getrandom::fill(&mut [0u8; 16]).unwrap();
+        assert_eq!(observed(), expected);
+    }
+}
+
+#[cfg(feature = "defmt")]
+mod defmt {
+    fn assert_format<T: ::defmt::Format>() {}
+
+    #[test]
+    fn test_format() {
+        assert_format::<getrandom::Error>();
+    }
+}
+
+#[cfg(feature = "rustix")]
+mod rustix {
+    use ::rustix::io::Errno;
+    use getrandom::Error;
+
+    #[test]
+    fn test_from_errno() {
+        for errno in [Errno::INTR, Errno::NOSYS, Errno::PERM] {
+            let err = Error::from(errno);
+            assert_eq!(err.raw_os_error(), Some(errno.raw_os_error()));
+            assert_eq!(Errno::from_raw_os_error(err.raw_os_error().unwrap()), errno);
+        }
+    }
 }